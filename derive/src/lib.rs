@@ -3,45 +3,108 @@ extern crate proc_macro;
 use darling::{FromDeriveInput, FromMeta};
 use darling::{
     FromField, FromVariant,
-    ast::{self, Data, Fields},
+    ast::{self, Data, Fields, Style},
 };
 use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
+use syn::punctuated::Punctuated;
 use syn::{
-    Attribute, DeriveInput, Expr, ExprLit, Ident, Lit, Meta, PathArguments, Type, TypePath,
-    parse_macro_input,
+    Attribute, DeriveInput, Expr, ExprLit, Generics, Ident, Index, Lit, LitStr, Meta,
+    PathArguments, Token, Type, TypePath, WherePredicate, parse_macro_input, parse_quote,
 };
 mod serde_parse;
 
+use serde_parse::{RenameRule, SerdeAttrs, SerdeDefault};
+
 #[proc_macro_derive(TomlInput, attributes(toml_input))]
 pub fn derive(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(tokens as DeriveInput);
+    match expand(&input) {
+        Ok(token) => token.into(),
+        Err(err) => err.write_errors().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> darling::Result<TokenStream> {
+    if let syn::Data::Union(data) = &input.data {
+        let err = darling::Error::custom("TomlInput cannot be derived for unions");
+        return Err(err.with_span(&data.union_token));
+    }
     let StructRaw {
         ident,
+        generics,
         attrs,
         data,
         enum_style,
         option_style,
-    } = StructRaw::from_derive_input(&input).unwrap();
+        flex_limit,
+        commented,
+        comment_style,
+        bound,
+    } = StructRaw::from_derive_input(input)?;
+    let needs_default = match &data {
+        Data::Struct(fields) if fields.style == Style::Struct => {
+            quote_struct_default(&SerdeAttrs::new(&attrs), fields).is_none()
+        }
+        _ => true,
+    };
+    let generics = bound_generics(&ident, generics, bound, needs_default)?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let config = Config {
         enum_style,
         option_style,
+        flex_limit,
+        commented,
+        comment_style,
         ..Default::default()
     };
     let schema_token;
     let value_token;
     match data {
+        Data::Enum(variants) if variants.is_empty() => {
+            let err =
+                darling::Error::custom("TomlInput cannot be derived for an enum without variants");
+            return Err(err.with_span(&ident));
+        }
         Data::Enum(variants) => {
-            schema_token = quote_enum_schema(&ident, &attrs, variants, config);
-            value_token = quote_enum_value();
+            schema_token = quote_enum_schema(&ident, &attrs, variants.clone(), config)?;
+            value_token = quote_enum_value(&attrs, variants)?;
+        }
+        Data::Struct(fields) if fields.style == Style::Unit => {
+            schema_token = quote_unit_schema(&ident, &attrs, config);
+            value_token = quote_unit_value();
+        }
+        Data::Struct(fields) if fields.style == Style::Tuple && fields.len() > 1 => {
+            schema_token = quote_tuple_schema(&ident, &attrs, config);
+            value_token = quote_tuple_value();
+        }
+        Data::Struct(fields)
+            if fields.style == Style::Tuple || SerdeAttrs::new(&attrs).transparent =>
+        {
+            let mut inner = fields
+                .into_iter()
+                .enumerate()
+                .filter(|(_, field)| !SerdeAttrs::new(&field.attrs).is_skipped());
+            let (Some((index, field)), None) = (inner.next(), inner.next()) else {
+                let err = darling::Error::custom(
+                    "transparent struct must have exactly one field that is not skipped",
+                );
+                return Err(err.with_span(&ident));
+            };
+            let member = match &field.ident {
+                Some(ident) => quote! {#ident},
+                None => Index::from(index).into_token_stream(),
+            };
+            schema_token = quote_newtype_schema(&attrs, &field, &member, config);
+            value_token = quote_newtype_value(&member);
         }
         Data::Struct(fields) => {
-            schema_token = quote_struct_schema(&ident, &attrs, fields.clone(), config);
-            value_token = quote_struct_value(&attrs, fields.clone());
+            schema_token = quote_struct_schema(&ident, &attrs, fields.clone(), config)?;
+            value_token = quote_struct_value(&attrs, fields)?;
         }
     }
     let token = quote! {
-        impl toml_input::TomlInput for #ident {
+        impl #impl_generics toml_input::TomlInput for #ident #ty_generics #where_clause {
             fn schema() -> Result<toml_input::Schema, toml_input::Error> {
                 use toml;
                 use toml_input::schema;
@@ -53,7 +116,7 @@ pub fn derive(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
             }
         }
     };
-    token.into()
+    Ok(token)
 }
 
 fn quote_enum_schema(
@@ -61,62 +124,214 @@ fn quote_enum_schema(
     attrs: &[Attribute],
     variants: Vec<VariantRaw>,
     config: Config,
-) -> TokenStream {
+) -> darling::Result<TokenStream> {
     let enum_ident = ident;
+    let enum_serde = SerdeAttrs::new(attrs);
     let enum_docs = parse_docs(attrs);
     let inner_type = enum_ident.to_string();
     let mut tokens = Vec::new();
-    for variant in variants {
-        let VariantRaw { attrs, enum_style } = variant;
+    for variant in variants.clone() {
+        let VariantRaw {
+            ident,
+            attrs,
+            fields,
+            enum_style,
+            commented,
+            comment_style,
+        } = variant;
         let variant_docs = parse_docs(&attrs);
         let variant_config = Config {
             enum_style: enum_style.or(config.enum_style.clone()),
+            flex_limit: config.flex_limit,
+            commented,
+            comment_style,
             ..Default::default()
         };
         let enum_style_token = variant_config.enum_style_token(quote! {variant});
+        let comment_token = variant_config.comment_token(quote! {variant});
+        let variant_serde = SerdeAttrs::new(&attrs);
+        let variant_tag = variant_serde.variant_name(&enum_serde, &ident);
+        let variant_rule = variant_serde.variant_fields_rule(&enum_serde);
+        let example_token = quote_variant_example(&ident, &fields);
+        let table_token =
+            quote_variant_table(&ident, &attrs, &variant_rule, fields, &variant_config)?;
         let variant_token = quote! {
             let mut variant = schema::VariantSchema::default();
             variant.docs = #variant_docs.to_string();
-            let value = variant_iter.next().ok_or(toml_input::Error::EnumEmpty)?;
-            let tag = std::convert::AsRef::as_ref(&value).to_string();
+            let value: Self = #example_token;
+            let tag = #variant_tag.to_string();
             let raw = toml::Value::try_from(value)?;
             let prim_value = toml_input::PrimValue {tag, raw: Some(raw)};
             variant.value = prim_value;
             #enum_style_token
+            #comment_token
+            #table_token
             prim_schema.variants.push(variant);
         };
         tokens.push(variant_token);
     }
     let enum_style_token = config.enum_style_token(quote! {meta});
+    let comment_token = config.comment_token(quote! {meta});
+    let tagging = enum_serde.tagging();
+    let tag_token = quote_variant_tag(&enum_serde, &variants, quote! {default});
     let enum_token = quote! {
-        use strum::IntoEnumIterator;
-        let default = <#enum_ident as Default>::default();
+        use std::str::FromStr;
+        use toml_input::config::OptionStyle;
+        let default = <Self as Default>::default();
         let mut prim_schema = schema::PrimSchema::default();
         let mut meta = schema::Meta::default();
         meta.wrap_type = "".to_string();
         meta.inner_type = #inner_type.to_string();
-        let tag = default.as_ref().to_string();
+        let tag = #tag_token.to_string();
         let raw = toml::Value::try_from(default)?;
         meta.inner_default = toml_input::PrimValue{tag, raw: Some(raw)};
         meta.defined_docs = #enum_docs.to_string();
-        #enum_style_token;
+        meta.tagging = #tagging;
+        #enum_style_token
+        #comment_token
         prim_schema.meta = meta;
-        let mut variant_iter = #enum_ident::iter();
         prim_schema.variants = Vec::new();
         #(#tokens)*
+        prim_schema.set_default(<Self as Default>::default().into_value()?);
         Ok(schema::Schema::Prim(prim_schema))
     };
-    enum_token
+    Ok(enum_token)
+}
+
+fn quote_variant_example(ident: &Ident, fields: &Fields<FieldRaw>) -> TokenStream {
+    match fields.style {
+        Style::Unit => quote! { Self::#ident },
+        Style::Tuple => {
+            let defaults = fields.iter().map(|_| quote! { Default::default() });
+            quote! { Self::#ident(#(#defaults),*) }
+        }
+        Style::Struct => {
+            let idents = fields.iter().map(|field| &field.ident);
+            let defaults = fields
+                .iter()
+                .map(|field| quote_field_default(&SerdeAttrs::new(&field.attrs)));
+            quote! { Self::#ident { #(#idents: #defaults),* } }
+        }
+    }
 }
 
-fn quote_enum_value() -> TokenStream {
+fn quote_variant_tag(
+    enum_serde: &SerdeAttrs,
+    variants: &[VariantRaw],
+    value: TokenStream,
+) -> TokenStream {
+    let mut arms = Vec::new();
+    for variant in variants {
+        let ident = &variant.ident;
+        let tag = SerdeAttrs::new(&variant.attrs).variant_name(enum_serde, ident);
+        let arm = match variant.fields.style {
+            Style::Unit => quote! { Self::#ident => #tag, },
+            Style::Tuple => quote! { Self::#ident(..) => #tag, },
+            Style::Struct => quote! { Self::#ident { .. } => #tag, },
+        };
+        arms.push(arm);
+    }
+    quote! {
+        match &#value {
+            #(#arms)*
+        }
+    }
+}
+
+fn quote_variant_table(
+    ident: &Ident,
+    attrs: &[Attribute],
+    variant_rule: &RenameRule,
+    fields: Fields<FieldRaw>,
+    config: &Config,
+) -> darling::Result<TokenStream> {
+    let variant_docs = parse_docs(attrs);
+    let inner_type = ident.to_string();
+    let token = match fields.style {
+        Style::Struct => {
+            let tokens = quote_fields_schema(variant_rule, fields, config, false)?;
+            quote! {
+                let mut table = schema::TableSchema::default();
+                table.meta.inner_type = #inner_type.to_string();
+                table.meta.defined_docs = #variant_docs.to_string();
+                #(#tokens)*
+                variant.schema = Some(table);
+            }
+        }
+        Style::Tuple if fields.len() == 1 => {
+            let ty = &fields.fields[0].ty;
+            quote! {
+                if let schema::Schema::Table(mut table) = <#ty as toml_input::TomlInput>::schema()? {
+                    let docs = #variant_docs;
+                    if !docs.is_empty() {
+                        table.meta.defined_docs = docs.to_string();
+                    }
+                    variant.schema = Some(table);
+                }
+            }
+        }
+        _ => TokenStream::new(),
+    };
+    Ok(token)
+}
+
+fn quote_enum_value(
+    enum_attrs: &[Attribute],
+    variants: Vec<VariantRaw>,
+) -> darling::Result<TokenStream> {
+    let enum_serde = SerdeAttrs::new(enum_attrs);
+    let tag_token = quote_variant_tag(&enum_serde, &variants, quote! {self});
+    let mut arms = Vec::new();
+    for variant in variants {
+        let VariantRaw {
+            ident,
+            attrs,
+            fields,
+            ..
+        } = variant;
+        match fields.style {
+            Style::Struct => {
+                let variant_rule = SerdeAttrs::new(&attrs).variant_fields_rule(&enum_serde);
+                let idents: Vec<_> = fields
+                    .iter()
+                    .filter(|field| !SerdeAttrs::new(&field.attrs).is_skipped())
+                    .map(|field| field.ident.clone())
+                    .collect();
+                let tokens = quote_fields_value(&variant_rule, fields, TokenStream::new())?;
+                arms.push(quote! {
+                    Self::#ident { #(#idents,)* .. } => {
+                        let mut table = toml_input::TableValue::default();
+                        #(#tokens)*
+                        let variant = toml_input::VariantValue { prim, table };
+                        Ok(toml_input::Value::Variant(variant))
+                    }
+                });
+            }
+            Style::Tuple if fields.len() == 1 => {
+                arms.push(quote! {
+                    Self::#ident(inner) => match inner.into_value()? {
+                        toml_input::Value::Table(table) => {
+                            let variant = toml_input::VariantValue { prim, table };
+                            Ok(toml_input::Value::Variant(variant))
+                        }
+                        _ => Ok(toml_input::Value::Prim(prim)),
+                    }
+                });
+            }
+            _ => {}
+        }
+    }
     let enum_token = quote! {
-        let tag = self.as_ref().to_string();
-        let raw = toml::Value::try_from(self)?;
+        let tag = #tag_token.to_string();
+        let raw = toml::Value::try_from(&self)?;
         let prim = toml_input::PrimValue {tag, raw: Some(raw)};
-        Ok(toml_input::Value::Prim(prim))
+        #[allow(unreachable_patterns)]
+        match self {
+            #(#arms)*
+            _ => Ok(toml_input::Value::Prim(prim)),
+        }
     };
-    enum_token
+    Ok(enum_token)
 }
 
 fn quote_struct_schema(
@@ -124,11 +339,114 @@ fn quote_struct_schema(
     attrs: &[Attribute],
     fields: Fields<FieldRaw>,
     config: Config,
-) -> TokenStream {
+) -> darling::Result<TokenStream> {
     let struct_ident = ident;
     let struct_docs = parse_docs(attrs);
     let inner_type = struct_ident.to_string();
-    let struct_rule = serde_parse::rename_rule(attrs);
+    let struct_serde = SerdeAttrs::new(attrs);
+    let default_token = quote_struct_default(&struct_serde, &fields)
+        .unwrap_or_else(|| quote! { <Self as Default>::default() });
+    let container_default = struct_serde.default.is_some();
+    let mut default_fields_token = TokenStream::new();
+    if container_default {
+        default_fields_token = quote! {
+            let mut default_fields = std::collections::BTreeMap::new();
+            if let toml_input::Value::Table(default_table) = default_value.clone() {
+                for field in default_table.fields {
+                    default_fields.insert(field.ident, field.value);
+                }
+            }
+        };
+    }
+    let tokens = quote_fields_schema(&struct_serde.rename_all, fields, &config, container_default)?;
+    let enum_style_token = config.enum_style_token(quote! {meta});
+    let option_style_token = config.option_style_token(quote! {meta});
+    let comment_token = config.comment_token(quote! {meta});
+    let struct_token = quote! {
+        use std::str::FromStr;
+        use toml_input::config::OptionStyle;
+        let default: Self = #default_token;
+        let default_value = toml_input::TomlInput::into_value(default)?;
+        #default_fields_token
+        let mut table = schema::TableSchema::default();
+        let mut meta = schema::Meta::default();
+        meta.wrap_type = "".to_string();
+        meta.inner_type = #inner_type.to_string();
+        meta.inner_default = default_value.into_prim();
+        meta.defined_docs = #struct_docs.to_string();
+        #enum_style_token
+        #option_style_token
+        #comment_token
+        table.meta = meta;
+        table.fields = Vec::new();
+        #(#tokens)*
+        Ok(schema::Schema::Table(table))
+    };
+    Ok(struct_token)
+}
+
+fn quote_struct_value(
+    attrs: &[Attribute],
+    fields: Fields<FieldRaw>,
+) -> darling::Result<TokenStream> {
+    let struct_rule = SerdeAttrs::new(attrs).rename_all;
+    let tokens = quote_fields_value(&struct_rule, fields, quote! {self.})?;
+    let struct_token = quote! {
+        let mut table = toml_input::TableValue::default();
+        #(#tokens)*
+        Ok(toml_input::Value::Table(table))
+    };
+    Ok(struct_token)
+}
+
+fn quote_fields_value(
+    struct_rule: &RenameRule,
+    fields: Fields<FieldRaw>,
+    receiver: TokenStream,
+) -> darling::Result<Vec<TokenStream>> {
+    let mut tokens = Vec::new();
+    for field in fields {
+        let FieldRaw {
+            ident,
+            attrs,
+            ty,
+            removed,
+            ..
+        } = field;
+        let field_serde = SerdeAttrs::new(&attrs);
+        if field_serde.is_skipped() || removed {
+            continue;
+        }
+        let Some(field_ident) = ident else {
+            return Err(darling::Error::custom("expected a named field").with_span(&ty));
+        };
+        let field_name = field_serde.field_name(struct_rule, &field_ident);
+        let field_flatten = field_serde.flatten;
+        let mut field_token = quote! {
+            let mut field = toml_input::FieldValue::default();
+            field.ident = #field_name.to_string();
+            field.flat = #field_flatten;
+            field.value = #receiver #field_ident.into_value()?;
+            table.fields.push(field);
+        };
+        if let Some(skip_if) = &field_serde.skip_serializing_if {
+            field_token = quote! {
+                if !#skip_if(&#receiver #field_ident) {
+                    #field_token
+                }
+            };
+        }
+        tokens.push(field_token);
+    }
+    Ok(tokens)
+}
+
+fn quote_fields_schema(
+    struct_rule: &RenameRule,
+    fields: Fields<FieldRaw>,
+    config: &Config,
+    container_default: bool,
+) -> darling::Result<Vec<TokenStream>> {
     let mut tokens = Vec::new();
     for field in fields {
         let FieldRaw {
@@ -138,102 +456,246 @@ fn quote_struct_schema(
             enum_style,
             option_style,
             inner_default,
+            commented,
+            comment_style,
+            renamed_from,
+            since,
+            removed,
+            type_name,
         } = field;
-        if serde_parse::skip(&attrs) {
+        let field_serde = SerdeAttrs::new(&attrs);
+        if field_serde.is_skipped() {
             continue;
         }
-        let field_ident = ident.unwrap();
+        let Some(field_ident) = ident else {
+            return Err(darling::Error::custom("expected a named field").with_span(&ty));
+        };
+        if removed && renamed_from.is_some() {
+            let message = "a field cannot be both `removed` and `renamed_from`";
+            return Err(darling::Error::custom(message).with_span(&field_ident));
+        }
+        if since.is_some() && !removed && renamed_from.is_none() {
+            let message = "`since` requires `renamed_from` or `removed`";
+            return Err(darling::Error::custom(message).with_span(&field_ident));
+        }
+        let renamed_from_token = match renamed_from {
+            Some(old) => quote! { field.renamed_from = Some(#old.to_string()); },
+            None => TokenStream::new(),
+        };
+        let since_token = match since {
+            Some(since) => quote! { field.since = Some(#since.to_string()); },
+            None => TokenStream::new(),
+        };
+        let aliases = &field_serde.aliases;
+        let aliases_token = if aliases.is_empty() {
+            TokenStream::new()
+        } else {
+            quote! { field.aliases = vec![#(#aliases.to_string()),*]; }
+        };
+        let has_default = container_default || field_serde.default.is_some();
+        let type_name_token = match type_name {
+            Some(name) => quote! { field.schema.meta_mut().type_name = Some(#name.to_string()); },
+            None => TokenStream::new(),
+        };
         let field_docs = parse_docs(&attrs);
-        let field_rule = serde_parse::rename_rule(&attrs);
-        let field_name = field_ident.to_string();
-        let field_name = struct_rule.case_to(field_name);
-        let field_name = field_rule.alias(field_name);
-        let field_flatten = serde_parse::flatten(&attrs);
+        let field_name = field_serde.field_name(struct_rule, &field_ident);
+        let field_flatten = field_serde.flatten;
         let field_config = Config {
             enum_style: enum_style.or(config.enum_style.clone()),
             option_style: option_style.or(config.option_style.clone()),
             inner_default,
+            flex_limit: config.flex_limit,
+            commented,
+            comment_style,
         };
         let enum_style_token = field_config.enum_style_token(quote! {field});
         let option_style_token = field_config.option_style_token(quote! {field});
+        let comment_token = field_config.comment_token(quote! {field});
         let inner_type = extract_inner_type(&ty);
         let inner_default_token = field_config.inner_default_token(quote! {field}, inner_type);
+        let default_token = match &field_serde.default {
+            Some(SerdeDefault::Path(path)) => quote! {
+                let value: #ty = #path();
+                field.schema.set_default(toml_input::TomlInput::into_value(value)?);
+            },
+            _ if container_default => quote! {
+                if let Some(value) = default_fields.remove(#field_name) {
+                    field.schema.set_default(value);
+                }
+            },
+            _ => TokenStream::new(),
+        };
         let field_token = quote! {
             let mut field = schema::FieldSchema::default();
             field.ident = #field_name.to_string();
             field.docs = #field_docs.to_string();
             field.flat = #field_flatten;
+            field.removed = #removed;
+            field.has_default = #has_default;
+            #renamed_from_token
+            #since_token
+            #aliases_token
             field.schema = <#ty as toml_input::TomlInput>::schema()?;
+            #type_name_token
+            #default_token
             #enum_style_token
             #option_style_token
+            #comment_token
             #inner_default_token
             table.fields.push(field);
         };
         tokens.push(field_token);
     }
+    Ok(tokens)
+}
+
+// Defaults that serde itself would use, without requiring `Self: Default`.
+fn quote_struct_default(
+    struct_serde: &SerdeAttrs,
+    fields: &Fields<FieldRaw>,
+) -> Option<TokenStream> {
+    match &struct_serde.default {
+        Some(SerdeDefault::Path(path)) => return Some(quote! { #path() }),
+        Some(SerdeDefault::Default) => return None,
+        None => {}
+    }
+    let mut inits = Vec::new();
+    for field in fields.iter() {
+        let field_serde = SerdeAttrs::new(&field.attrs);
+        let skipped = field_serde.skip || field_serde.skip_deserializing;
+        if field_serde.default.is_none() && !skipped {
+            return None;
+        }
+        let ident = &field.ident;
+        let default = quote_field_default(&field_serde);
+        inits.push(quote! { #ident: #default });
+    }
+    Some(quote! { Self { #(#inits),* } })
+}
+
+fn quote_field_default(field_serde: &SerdeAttrs) -> TokenStream {
+    match &field_serde.default {
+        Some(SerdeDefault::Path(path)) => quote! { #path() },
+        _ => quote! { Default::default() },
+    }
+}
+
+fn quote_newtype_schema(
+    attrs: &[Attribute],
+    field: &FieldRaw,
+    member: &TokenStream,
+    config: Config,
+) -> TokenStream {
+    let newtype_docs = parse_docs(attrs);
+    let ty = &field.ty;
     let enum_style_token = config.enum_style_token(quote! {meta});
     let option_style_token = config.option_style_token(quote! {meta});
-    let struct_token = quote! {
-        use std::str::FromStr;
+    let comment_token = config.comment_token(quote! {meta});
+    let newtype_token = quote! {
         use toml_input::config::OptionStyle;
-        let default = <#struct_ident as Default>::default();
-        let mut table = schema::TableSchema::default();
+        let default = <Self as Default>::default();
+        let mut schema = <#ty as toml_input::TomlInput>::schema()?;
+        if schema.is_prim() {
+            schema.meta_mut().inner_default = default.#member.into_value()?.into_prim();
+        }
+        let meta = schema.meta_mut();
+        let docs = #newtype_docs;
+        if !docs.is_empty() {
+            meta.defined_docs = docs.to_string();
+        }
+        #enum_style_token
+        #option_style_token
+        #comment_token
+        Ok(schema)
+    };
+    newtype_token
+}
+
+fn quote_newtype_value(member: &TokenStream) -> TokenStream {
+    quote! {
+        self.#member.into_value()
+    }
+}
+
+fn quote_tuple_schema(ident: &Ident, attrs: &[Attribute], config: Config) -> TokenStream {
+    let tuple_docs = parse_docs(attrs);
+    let inner_type = ident.to_string();
+    let enum_style_token = config.enum_style_token(quote! {meta});
+    let option_style_token = config.option_style_token(quote! {meta});
+    let comment_token = config.comment_token(quote! {meta});
+    let tuple_token = quote! {
+        use toml_input::config::OptionStyle;
+        let default = <Self as Default>::default();
+        let mut prim_schema = schema::PrimSchema::default();
         let mut meta = schema::Meta::default();
         meta.wrap_type = "".to_string();
         meta.inner_type = #inner_type.to_string();
         let raw = toml::Value::try_from(default)?;
         meta.inner_default = toml_input::PrimValue::new(raw);
-        meta.defined_docs = #struct_docs.to_string();
+        meta.defined_docs = #tuple_docs.to_string();
         #enum_style_token
         #option_style_token
+        #comment_token
+        prim_schema.meta = meta;
+        Ok(schema::Schema::Prim(prim_schema))
+    };
+    tuple_token
+}
+
+fn quote_tuple_value() -> TokenStream {
+    quote! {
+        let raw = toml::Value::try_from(self)?;
+        Ok(toml_input::Value::new_prim(raw))
+    }
+}
+
+fn quote_unit_schema(ident: &Ident, attrs: &[Attribute], config: Config) -> TokenStream {
+    let unit_docs = parse_docs(attrs);
+    let inner_type = ident.to_string();
+    let enum_style_token = config.enum_style_token(quote! {meta});
+    let option_style_token = config.option_style_token(quote! {meta});
+    let comment_token = config.comment_token(quote! {meta});
+    let unit_token = quote! {
+        use toml_input::config::OptionStyle;
+        let mut table = schema::TableSchema::default();
+        let mut meta = schema::Meta::default();
+        meta.wrap_type = "".to_string();
+        meta.inner_type = #inner_type.to_string();
+        let raw = toml::Value::Table(toml::Table::new());
+        meta.inner_default = toml_input::PrimValue::new(raw);
+        meta.defined_docs = #unit_docs.to_string();
+        #enum_style_token
+        #option_style_token
+        #comment_token
         table.meta = meta;
-        table.fields = Vec::new();
-        #(#tokens)*
         Ok(schema::Schema::Table(table))
     };
-    struct_token
+    unit_token
 }
 
-fn quote_struct_value(attrs: &[Attribute], fields: Fields<FieldRaw>) -> TokenStream {
-    let struct_rule = serde_parse::rename_rule(attrs);
-    let mut tokens = Vec::new();
-    for field in fields {
-        let FieldRaw { ident, attrs, .. } = field;
-        let field_ident = ident.unwrap();
-        let field_rule = serde_parse::rename_rule(&attrs);
-        let field_name = field_ident.to_string();
-        let field_name = struct_rule.case_to(field_name);
-        let field_name = field_rule.alias(field_name);
-        let field_flatten = serde_parse::flatten(&attrs);
-        let field_token = quote! {
-            let mut field = toml_input::FieldValue::default();
-            field.ident = #field_name.to_string();
-            field.flat = #field_flatten;
-            field.value = self.#field_ident.into_value()?;
-            table.fields.push(field);
-        };
-        tokens.push(field_token);
+fn quote_unit_value() -> TokenStream {
+    quote! {
+        Ok(toml_input::Value::Table(toml_input::TableValue::default()))
     }
-    let struct_token = quote! {
-        let mut table = toml_input::TableValue::default();
-        #(#tokens)*
-        Ok(toml_input::Value::Table(table))
-    };
-    struct_token
 }
 
 #[derive(Debug, Clone, FromDeriveInput)]
 #[darling(
-    supports(struct_named, enum_any),
+    supports(struct_any, enum_any),
     attributes(toml_input),
     forward_attrs(doc, serde)
 )]
 struct StructRaw {
     ident: Ident,
+    generics: Generics,
     attrs: Vec<Attribute>,
     data: ast::Data<VariantRaw, FieldRaw>,
     enum_style: Option<EnumStyle>,
     option_style: Option<OptionStyle>,
+    flex_limit: Option<usize>,
+    commented: Option<bool>,
+    comment_style: Option<CommentStyle>,
+    bound: Option<LitStr>,
 }
 
 #[derive(Debug, Clone, FromField)]
@@ -245,13 +707,51 @@ struct FieldRaw {
     enum_style: Option<EnumStyle>,
     option_style: Option<OptionStyle>,
     inner_default: Option<String>,
+    commented: Option<bool>,
+    comment_style: Option<CommentStyle>,
+    renamed_from: Option<String>,
+    since: Option<String>,
+    #[darling(default)]
+    removed: bool,
+    type_name: Option<String>,
 }
 
 #[derive(Debug, Clone, FromVariant)]
 #[darling(attributes(toml_input), forward_attrs(doc, serde))]
 struct VariantRaw {
+    ident: Ident,
     attrs: Vec<Attribute>,
+    fields: Fields<FieldRaw>,
     enum_style: Option<EnumStyle>,
+    commented: Option<bool>,
+    comment_style: Option<CommentStyle>,
+}
+
+fn bound_generics(
+    ident: &Ident,
+    mut generics: Generics,
+    bound: Option<LitStr>,
+    needs_default: bool,
+) -> syn::Result<Generics> {
+    if generics.params.is_empty() {
+        return Ok(generics);
+    }
+    let mut predicates: Vec<WherePredicate> = Vec::new();
+    if let Some(bound) = bound {
+        let parser = Punctuated::<WherePredicate, Token![,]>::parse_terminated;
+        predicates.extend(bound.parse_with(parser)?);
+    } else {
+        for param in generics.type_params() {
+            let param = &param.ident;
+            predicates.push(parse_quote! { #param: toml_input::TomlInput });
+        }
+    }
+    let (_, ty_generics, _) = generics.split_for_impl();
+    if needs_default {
+        predicates.push(parse_quote! { #ident #ty_generics: Default });
+    }
+    generics.make_where_clause().predicates.extend(predicates);
+    Ok(generics)
 }
 
 fn parse_docs(attrs: &[Attribute]) -> String {
@@ -260,43 +760,52 @@ fn parse_docs(attrs: &[Attribute]) -> String {
         if !attr.path().is_ident("doc") {
             continue;
         }
-        if let Meta::NameValue(name_value) = &attr.meta {
-            if let Expr::Lit(ExprLit {
+        if let Meta::NameValue(name_value) = &attr.meta
+            && let Expr::Lit(ExprLit {
                 lit: Lit::Str(lit_str),
                 ..
             }) = name_value.value.clone()
-            {
-                docs.push(lit_str.value());
-            }
+        {
+            docs.push(lit_str.value());
         }
     }
     docs.join("\n").to_string()
 }
 
 fn extract_inner_type(ty: &syn::Type) -> TokenStream {
-    if let Type::Path(TypePath { path, .. }) = ty {
-        if let Some(segment) = path.segments.last() {
-            if let PathArguments::AngleBracketed(args) = &segment.arguments {
-                if let Some(syn::GenericArgument::Type(inner_ty)) = args.args.first() {
-                    return inner_ty.into_token_stream();
-                }
-            }
-        }
+    if let Type::Path(TypePath { path, .. }) = ty
+        && let Some(segment) = path.segments.last()
+        && let PathArguments::AngleBracketed(args) = &segment.arguments
+        && let Some(syn::GenericArgument::Type(inner_ty)) = args.args.first()
+    {
+        return inner_ty.into_token_stream();
     }
     TokenStream::new()
 }
 
+const FLEX_LIMIT: usize = 4;
+
 #[derive(Clone, Default)]
 struct Config {
     enum_style: Option<EnumStyle>,
     option_style: Option<OptionStyle>,
     inner_default: Option<String>,
+    flex_limit: Option<usize>,
+    commented: Option<bool>,
+    comment_style: Option<CommentStyle>,
 }
 
 impl Config {
+    fn enum_style(&self) -> Option<EnumStyle> {
+        match (&self.enum_style, self.flex_limit) {
+            (None | Some(EnumStyle::Flex(None)), Some(limit)) => Some(EnumStyle::Flex(Some(limit))),
+            (enum_style, _) => enum_style.clone(),
+        }
+    }
+
     fn enum_style_token(&self, tag: TokenStream) -> TokenStream {
         let mut token = TokenStream::new();
-        if let Some(enum_style) = &self.enum_style {
+        if let Some(enum_style) = &self.enum_style() {
             token = quote! {
                 #tag.config.enum_style = Some(#enum_style);
             };
@@ -314,6 +823,21 @@ impl Config {
         token
     }
 
+    fn comment_token(&self, tag: TokenStream) -> TokenStream {
+        let mut token = TokenStream::new();
+        if let Some(commented) = self.commented {
+            token.extend(quote! {
+                #tag.config.commented = Some(#commented);
+            });
+        }
+        if let Some(comment_style) = &self.comment_style {
+            token.extend(quote! {
+                #tag.config.comment_style = Some(#comment_style);
+            });
+        }
+        token
+    }
+
     fn inner_default_token(&self, tag: TokenStream, inner_type: TokenStream) -> TokenStream {
         let mut token = TokenStream::new();
         if inner_type.is_empty() {
@@ -330,22 +854,42 @@ impl Config {
     }
 }
 
-#[derive(Debug, Clone, FromMeta, Default)]
+#[derive(Debug, Clone, Default)]
 enum EnumStyle {
     Single,
     #[default]
     Expand,
     Fold,
-    Flex,
-    Flex4,
-    Flex5,
-    Flex6,
-    Flex7,
-    Flex8,
-    Flex9,
-    Flex10,
-    Flex11,
-    Flex12,
+    Flex(Option<usize>),
+}
+
+impl EnumStyle {
+    const NAMES: &[&str] = &["single", "expand", "fold", "flex", "flex(<limit>)"];
+
+    fn parse_flex(value: &str) -> Option<usize> {
+        let limit = value.strip_prefix("flex")?;
+        let limit = match limit.strip_prefix('(') {
+            Some(limit) => limit.strip_suffix(')')?,
+            None => limit,
+        };
+        limit.trim().parse().ok()
+    }
+}
+
+impl FromMeta for EnumStyle {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let style = match value {
+            "single" => EnumStyle::Single,
+            "expand" => EnumStyle::Expand,
+            "fold" => EnumStyle::Fold,
+            "flex" => EnumStyle::Flex(None),
+            _ => match EnumStyle::parse_flex(value) {
+                Some(limit) => EnumStyle::Flex(Some(limit)),
+                None => return Err(unknown_value("enum_style", value, EnumStyle::NAMES)),
+            },
+        };
+        Ok(style)
+    }
 }
 
 impl ToTokens for EnumStyle {
@@ -354,28 +898,54 @@ impl ToTokens for EnumStyle {
             EnumStyle::Single => quote! { EnumStyle::Single },
             EnumStyle::Expand => quote! { EnumStyle::Expand },
             EnumStyle::Fold => quote! { EnumStyle::Fold },
-            EnumStyle::Flex => quote! { EnumStyle::Flex(4) },
-            EnumStyle::Flex4 => quote! { EnumStyle::Flex(4) },
-            EnumStyle::Flex5 => quote! { EnumStyle::Flex(5) },
-            EnumStyle::Flex6 => quote! { EnumStyle::Flex(6) },
-            EnumStyle::Flex7 => quote! { EnumStyle::Flex(7) },
-            EnumStyle::Flex8 => quote! { EnumStyle::Flex(8) },
-            EnumStyle::Flex9 => quote! { EnumStyle::Flex(9) },
-            EnumStyle::Flex10 => quote! { EnumStyle::Flex(10) },
-            EnumStyle::Flex11 => quote! { EnumStyle::Flex(11) },
-            EnumStyle::Flex12 => quote! { EnumStyle::Flex(12) },
+            EnumStyle::Flex(limit) => {
+                let limit = limit.unwrap_or(FLEX_LIMIT);
+                quote! { EnumStyle::Flex(#limit) }
+            }
         };
         tokens.extend(token);
     }
 }
 
-#[derive(Debug, Clone, FromMeta, Default)]
+impl ToTokens for serde_parse::Tagging {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        use serde_parse::Tagging;
+        let token = match self {
+            Tagging::External => quote! { schema::Tagging::External },
+            Tagging::Internal(tag) => quote! {
+                schema::Tagging::Internal { tag: #tag.to_string() }
+            },
+            Tagging::Adjacent(tag, content) => quote! {
+                schema::Tagging::Adjacent { tag: #tag.to_string(), content: #content.to_string() }
+            },
+            Tagging::Untagged => quote! { schema::Tagging::Untagged },
+        };
+        tokens.extend(token);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 enum OptionStyle {
     SkipNone,
     #[default]
     ExpandNone,
 }
 
+impl OptionStyle {
+    const NAMES: &[&str] = &["skip_none", "expand_none"];
+}
+
+impl FromMeta for OptionStyle {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let style = match value {
+            "skip_none" => OptionStyle::SkipNone,
+            "expand_none" => OptionStyle::ExpandNone,
+            _ => return Err(unknown_value("option_style", value, OptionStyle::NAMES)),
+        };
+        Ok(style)
+    }
+}
+
 impl ToTokens for OptionStyle {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let token = match self {
@@ -385,3 +955,49 @@ impl ToTokens for OptionStyle {
         tokens.extend(token);
     }
 }
+
+#[derive(Debug, Clone, Default)]
+enum CommentStyle {
+    #[default]
+    Show,
+    Hide,
+    Annotate,
+}
+
+impl CommentStyle {
+    const NAMES: &[&str] = &["show", "hide", "annotate"];
+}
+
+impl FromMeta for CommentStyle {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let style = match value {
+            "show" => CommentStyle::Show,
+            "hide" => CommentStyle::Hide,
+            "annotate" => CommentStyle::Annotate,
+            _ => return Err(unknown_value("comment_style", value, CommentStyle::NAMES)),
+        };
+        Ok(style)
+    }
+}
+
+impl ToTokens for CommentStyle {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let token = match self {
+            CommentStyle::Show => quote! {toml_input::config::CommentStyle::Show},
+            CommentStyle::Hide => quote! {toml_input::config::CommentStyle::Hide},
+            CommentStyle::Annotate => quote! {toml_input::config::CommentStyle::Annotate},
+        };
+        tokens.extend(token);
+    }
+}
+
+fn unknown_value(name: &str, value: &str, names: &[&str]) -> darling::Error {
+    let expected = names
+        .iter()
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    darling::Error::custom(format!(
+        "unknown {name} `{value}`, expected one of: {expected}"
+    ))
+}
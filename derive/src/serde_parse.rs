@@ -1,149 +1,401 @@
-use convert_case::{Case, Casing};
-use regex::Regex;
-use syn::{Attribute, Meta, MetaList};
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, ExprPath, Ident, LitStr, Token};
 
-#[derive(Default)]
-pub struct RenameRule {
-    pub case: Option<Case<'static>>,
-    pub alias: Option<String>,
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RenameRule {
+    #[default]
+    None,
+    LowerCase,
+    UpperCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
 }
 
 impl RenameRule {
-    pub fn new_case(case: Case<'static>) -> Self {
-        RenameRule {
-            case: Some(case),
-            alias: None,
-        }
+    pub fn from_str(text: &str) -> Option<Self> {
+        use RenameRule::*;
+        let rule = match text {
+            "lowercase" => LowerCase,
+            "UPPERCASE" => UpperCase,
+            "PascalCase" => PascalCase,
+            "camelCase" => CamelCase,
+            "snake_case" => SnakeCase,
+            "SCREAMING_SNAKE_CASE" => ScreamingSnakeCase,
+            "kebab-case" => KebabCase,
+            "SCREAMING-KEBAB-CASE" => ScreamingKebabCase,
+            _ => return Option::None,
+        };
+        Some(rule)
     }
 
-    pub fn case_to(&self, origin: String) -> String {
-        if let Some(case) = self.case {
-            origin.to_case(case)
-        } else {
-            origin
+    // same conversion as serde_derive, fields are expected in snake_case
+    pub fn apply_to_field(&self, field: &str) -> String {
+        use RenameRule::*;
+        match self {
+            None | LowerCase | SnakeCase => field.to_string(),
+            UpperCase | ScreamingSnakeCase => field.to_ascii_uppercase(),
+            PascalCase => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            CamelCase => {
+                let pascal = PascalCase.apply_to_field(field);
+                lower_first(&pascal)
+            }
+            KebabCase => field.replace('_', "-"),
+            ScreamingKebabCase => ScreamingSnakeCase.apply_to_field(field).replace('_', "-"),
         }
     }
 
-    pub fn alias(&self, origin: String) -> String {
-        if let Some(alias) = &self.alias {
-            alias.to_string()
-        } else {
-            origin
+    // same conversion as serde_derive, variants are expected in PascalCase
+    pub fn apply_to_variant(&self, variant: &str) -> String {
+        use RenameRule::*;
+        match self {
+            None | PascalCase => variant.to_string(),
+            LowerCase => variant.to_ascii_lowercase(),
+            UpperCase => variant.to_ascii_uppercase(),
+            CamelCase => lower_first(variant),
+            SnakeCase => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            ScreamingSnakeCase => SnakeCase.apply_to_variant(variant).to_ascii_uppercase(),
+            KebabCase => SnakeCase.apply_to_variant(variant).replace('_', "-"),
+            ScreamingKebabCase => ScreamingSnakeCase
+                .apply_to_variant(variant)
+                .replace('_', "-"),
         }
     }
 }
 
-pub fn rename_rule(attrs: &[Attribute]) -> RenameRule {
-    let mut rule = RenameRule::default();
-    if let Some(text) = parse_serde_text(attrs) {
-        let text = text.trim();
-        let rename_text = parse_rename(text).unwrap_or("".to_string());
-        rule = match rename_text.as_str() {
-            "lowercase" => RenameRule::new_case(Case::Lower),
-            "UPPERCASE" => RenameRule::new_case(Case::Upper),
-            "PascalCase" => RenameRule::new_case(Case::Pascal),
-            "camelCase" => RenameRule::new_case(Case::Camel),
-            "snake_case" => RenameRule::new_case(Case::Snake),
-            "SCREAMING_SNAKE_CASE" => RenameRule::new_case(Case::UpperSnake),
-            "kebab-case" => RenameRule::new_case(Case::Kebab),
-            "SCREAMING-KEBAB-CASE" => RenameRule::new_case(Case::UpperKebab),
-            _ if text.is_empty() => RenameRule {
-                alias: Some(text.to_string()),
-                ..Default::default()
-            },
-            _ => RenameRule::default(),
-        };
+fn lower_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
     }
-    rule
 }
 
-pub fn flatten(attrs: &[Attribute]) -> bool {
-    if let Some(text) = parse_serde_text(attrs) {
-        let re = Regex::new(r",?s*flatten").unwrap();
-        return re.is_match(&text);
-    }
-    false
+#[derive(Debug, Clone, PartialEq)]
+pub enum SerdeDefault {
+    Default,
+    Path(ExprPath),
 }
 
-pub fn skip(attrs: &[Attribute]) -> bool {
-    if let Some(text) = parse_serde_text(attrs) {
-        let re = Regex::new(r",?s*skip").unwrap();
-        return re.is_match(&text);
-    }
-    false
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tagging {
+    External,
+    Internal(String),
+    Adjacent(String, String),
+    Untagged,
 }
 
-fn parse_serde_text(attrs: &[Attribute]) -> Option<String> {
-    for attr in attrs {
-        if !attr.path().is_ident("serde") {
-            continue;
+/// The subset of `#[serde(...)]` that changes what serde reads and writes.
+#[derive(Debug, Clone, Default)]
+pub struct SerdeAttrs {
+    pub rename: Option<String>,
+    pub rename_all: RenameRule,
+    pub rename_all_fields: RenameRule,
+    pub aliases: Vec<String>,
+    pub skip: bool,
+    pub skip_serializing: bool,
+    pub skip_deserializing: bool,
+    pub skip_serializing_if: Option<ExprPath>,
+    pub default: Option<SerdeDefault>,
+    pub flatten: bool,
+    pub transparent: bool,
+    pub tag: Option<String>,
+    pub content: Option<String>,
+    pub untagged: bool,
+}
+
+impl SerdeAttrs {
+    /// Malformed attributes are ignored here, serde's own derive reports them.
+    pub fn new(attrs: &[Attribute]) -> Self {
+        Self::parse(attrs).unwrap_or_default()
+    }
+
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut serde = SerdeAttrs::default();
+        for attr in attrs {
+            if !attr.path().is_ident("serde") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| serde.parse_meta(meta))?;
+        }
+        Ok(serde)
+    }
+
+    fn parse_meta(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        let path = &meta.path;
+        if path.is_ident("rename") {
+            if let Some(name) = parse_serialize(&meta)? {
+                self.rename = Some(name);
+            }
+        } else if path.is_ident("rename_all") {
+            if let Some(text) = parse_serialize(&meta)? {
+                self.rename_all = parse_rule(&meta, &text)?;
+            }
+        } else if path.is_ident("rename_all_fields") {
+            if let Some(text) = parse_serialize(&meta)? {
+                self.rename_all_fields = parse_rule(&meta, &text)?;
+            }
+        } else if path.is_ident("alias") {
+            self.aliases.push(parse_str(&meta)?);
+        } else if path.is_ident("skip") {
+            self.skip = true;
+        } else if path.is_ident("skip_serializing") {
+            self.skip_serializing = true;
+        } else if path.is_ident("skip_deserializing") {
+            self.skip_deserializing = true;
+        } else if path.is_ident("skip_serializing_if") {
+            let text = parse_str(&meta)?;
+            self.skip_serializing_if = Some(syn::parse_str(&text)?);
+        } else if path.is_ident("default") {
+            if meta.input.peek(Token![=]) {
+                let text = parse_str(&meta)?;
+                self.default = Some(SerdeDefault::Path(syn::parse_str(&text)?));
+            } else {
+                self.default = Some(SerdeDefault::Default);
+            }
+        } else if path.is_ident("flatten") {
+            self.flatten = true;
+        } else if path.is_ident("transparent") {
+            self.transparent = true;
+        } else if path.is_ident("tag") {
+            self.tag = Some(parse_str(&meta)?);
+        } else if path.is_ident("content") {
+            self.content = Some(parse_str(&meta)?);
+        } else if path.is_ident("untagged") {
+            self.untagged = true;
+        } else {
+            skip_meta(&meta)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        self.skip || self.skip_serializing || self.skip_deserializing
+    }
+
+    pub fn field_name(&self, rule: &RenameRule, ident: &Ident) -> String {
+        match &self.rename {
+            Some(name) => name.clone(),
+            None => rule.apply_to_field(&ident.unraw().to_string()),
+        }
+    }
+
+    pub fn variant_name(&self, container: &SerdeAttrs, ident: &Ident) -> String {
+        match &self.rename {
+            Some(name) => name.clone(),
+            None => container
+                .rename_all
+                .apply_to_variant(&ident.unraw().to_string()),
+        }
+    }
+
+    /// The rule for the fields of a struct variant, `self` being the variant.
+    pub fn variant_fields_rule(&self, container: &SerdeAttrs) -> RenameRule {
+        if self.rename_all != RenameRule::None {
+            self.rename_all
+        } else {
+            container.rename_all_fields
+        }
+    }
+
+    pub fn tagging(&self) -> Tagging {
+        if self.untagged {
+            return Tagging::Untagged;
         }
-        if let Meta::List(MetaList { tokens, .. }) = attr.meta.clone() {
-            return Some(tokens.to_string());
+        match (&self.tag, &self.content) {
+            (Some(tag), Some(content)) => Tagging::Adjacent(tag.clone(), content.clone()),
+            (Some(tag), None) => Tagging::Internal(tag.clone()),
+            _ => Tagging::External,
         }
     }
-    None
 }
 
-fn parse_rename(text: &str) -> Option<String> {
-    None.or(parse_rename_simple(text))
-        .or(parse_rename_all(text))
-        .or(parse_rename_serialize(text))
-        .or(parse_rename_all_serialize(text))
+fn parse_str(meta: &ParseNestedMeta) -> syn::Result<String> {
+    let lit: LitStr = meta.value()?.parse()?;
+    Ok(lit.value())
 }
 
-// serde(rename="ser_name")
-fn parse_rename_simple(text: &str) -> Option<String> {
-    let re = Regex::new(r#"rename\s*=\s*"(?<value>.+)""#).unwrap();
-    re.captures(text).map(|cap| cap["value"].to_string())
+// rename = "name" | rename(serialize = "ser", deserialize = "de")
+fn parse_serialize(meta: &ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(Token![=]) {
+        return parse_str(meta).map(Some);
+    }
+    let mut serialize = None;
+    meta.parse_nested_meta(|nested| {
+        if nested.path.is_ident("serialize") {
+            serialize = Some(parse_str(&nested)?);
+        } else {
+            parse_str(&nested)?;
+        }
+        Ok(())
+    })?;
+    Ok(serialize)
+}
+
+fn parse_rule(meta: &ParseNestedMeta, text: &str) -> syn::Result<RenameRule> {
+    RenameRule::from_str(text).ok_or_else(|| meta.error(format!("unknown rename rule `{text}`")))
 }
 
-// rename(serialize = "ser_name")
-fn parse_rename_serialize(text: &str) -> Option<String> {
-    let re = Regex::new(r#"rename\s*(\(|.*,)\s*serialize\s*=\s*"(?<value>.+?)""#).unwrap();
-    re.captures(text).map(|cap| cap["value"].to_string())
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(skip_meta_owned)?;
+    }
+    Ok(())
 }
 
-// serde(rename_all="xxx")
-fn parse_rename_all(text: &str) -> Option<String> {
-    let re = Regex::new(r#"rename_all\s*=\s*"(?<value>.+)""#).unwrap();
-    re.captures(text).map(|cap| cap["value"].to_string())
+fn skip_meta_owned(meta: ParseNestedMeta) -> syn::Result<()> {
+    skip_meta(&meta)
 }
 
-// rename_all(serialize = "ser_name")
-fn parse_rename_all_serialize(text: &str) -> Option<String> {
-    let re = Regex::new(r#"rename_all\s*(\(|.*,)\s*serialize\s*=\s*"(?<value>.+?)""#).unwrap();
-    re.captures(text).map(|cap| cap["value"].to_string())
+#[cfg(test)]
+fn parse_test(attr: Attribute) -> SerdeAttrs {
+    SerdeAttrs::parse(&[attr]).unwrap()
 }
 
 #[test]
-fn test_rename_simple() {
-    let text = r#"rename="ser_name""#;
-    assert_eq!("ser_name", parse_rename_simple(text).unwrap())
+fn test_rename() {
+    let serde = parse_test(syn::parse_quote!(#[serde(rename = "ser_name")]));
+    assert_eq!(Some("ser_name".to_string()), serde.rename);
+    let serde = parse_test(syn::parse_quote!(#[serde(rename(serialize = "ser_name"))]));
+    assert_eq!(Some("ser_name".to_string()), serde.rename);
+    let serde = parse_test(syn::parse_quote!(
+        #[serde(rename(deserialize = "de_name", serialize = "ser_name"))]
+    ));
+    assert_eq!(Some("ser_name".to_string()), serde.rename);
+    let serde = parse_test(syn::parse_quote!(#[serde(rename(deserialize = "de_name"))]));
+    assert_eq!(None, serde.rename);
 }
 
 #[test]
 fn test_rename_all() {
-    let text = r#"rename_all="ser_name""#;
-    assert_eq!("ser_name", parse_rename_all(text).unwrap())
+    let serde = parse_test(syn::parse_quote!(#[serde(rename_all = "kebab-case")]));
+    assert_eq!(RenameRule::KebabCase, serde.rename_all);
+    let serde = parse_test(syn::parse_quote!(
+        #[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+    ));
+    assert_eq!(RenameRule::CamelCase, serde.rename_all);
+    let serde = parse_test(syn::parse_quote!(#[serde(rename_all_fields = "UPPERCASE")]));
+    assert_eq!(RenameRule::UpperCase, serde.rename_all_fields);
+    let attr: Attribute = syn::parse_quote!(#[serde(rename_all = "unknown")]);
+    assert!(SerdeAttrs::parse(&[attr]).is_err());
+}
+
+#[test]
+fn test_rename_rule() {
+    use RenameRule::*;
+    assert_eq!("s3Bucket", CamelCase.apply_to_field("s3_bucket"));
+    assert_eq!("S3Bucket", PascalCase.apply_to_field("s3_bucket"));
+    assert_eq!("S3-BUCKET", ScreamingKebabCase.apply_to_field("s3_bucket"));
+    assert_eq!("s3_bucket", SnakeCase.apply_to_variant("S3Bucket"));
+    assert_eq!("s3-bucket", KebabCase.apply_to_variant("S3Bucket"));
+    assert_eq!("s3Bucket", CamelCase.apply_to_variant("S3Bucket"));
+    assert_eq!("S3_BUCKET", ScreamingSnakeCase.apply_to_variant("S3Bucket"));
+}
+
+#[test]
+fn test_alias() {
+    let serde = parse_test(syn::parse_quote!(#[serde(alias = "a", alias = "b")]));
+    assert_eq!(vec!["a".to_string(), "b".to_string()], serde.aliases);
+}
+
+#[test]
+fn test_skip() {
+    let serde = parse_test(syn::parse_quote!(#[serde(skip)]));
+    assert!(serde.skip && serde.is_skipped());
+    let serde = parse_test(syn::parse_quote!(#[serde(skip_serializing)]));
+    assert!(serde.skip_serializing && serde.is_skipped());
+    let serde = parse_test(syn::parse_quote!(#[serde(skip_deserializing)]));
+    assert!(serde.skip_deserializing && serde.is_skipped());
+}
+
+#[test]
+fn test_skip_serializing_if() {
+    let serde = parse_test(syn::parse_quote!(#[serde(skip_serializing_if = "Option::is_none")]));
+    assert!(!serde.is_skipped());
+    let expected: ExprPath = syn::parse_quote!(Option::is_none);
+    assert_eq!(Some(expected), serde.skip_serializing_if);
+}
+
+#[test]
+fn test_default() {
+    let serde = parse_test(syn::parse_quote!(#[serde(default)]));
+    assert_eq!(Some(SerdeDefault::Default), serde.default);
+    let serde = parse_test(syn::parse_quote!(#[serde(default = "default_port")]));
+    let expected: ExprPath = syn::parse_quote!(default_port);
+    assert_eq!(Some(SerdeDefault::Path(expected)), serde.default);
+}
+
+#[test]
+fn test_flatten() {
+    let serde = parse_test(syn::parse_quote!(#[serde(flatten)]));
+    assert!(serde.flatten);
+    let serde = parse_test(syn::parse_quote!(#[serde(default, skip_serializing_if = "is_flat")]));
+    assert!(!serde.flatten);
+}
+
+#[test]
+fn test_transparent() {
+    let serde = parse_test(syn::parse_quote!(#[serde(transparent)]));
+    assert!(serde.transparent);
+}
+
+#[test]
+fn test_tagging() {
+    let serde = parse_test(syn::parse_quote!(#[serde(tag = "type")]));
+    assert_eq!(Tagging::Internal("type".to_string()), serde.tagging());
+    let serde = parse_test(syn::parse_quote!(#[serde(tag = "t", content = "c")]));
+    let expected = Tagging::Adjacent("t".to_string(), "c".to_string());
+    assert_eq!(expected, serde.tagging());
+    let serde = parse_test(syn::parse_quote!(#[serde(untagged)]));
+    assert_eq!(Tagging::Untagged, serde.tagging());
+    let serde = parse_test(syn::parse_quote!(#[serde(rename_all = "snake_case")]));
+    assert_eq!(Tagging::External, serde.tagging());
 }
 
 #[test]
-fn test_rename_serialize() {
-    let text = r#"rename(serialize = "ser_name")"#;
-    assert_eq!("ser_name", parse_rename_serialize(text).unwrap());
-    let text = r#"rename(serialize = "ser_name", deserialize = "de_name")"#;
-    assert_eq!("ser_name", parse_rename_serialize(text).unwrap());
-    let text = r#"rename(deserialize = "de_name", serialize = "ser_name")"#;
-    assert_eq!("ser_name", parse_rename_serialize(text).unwrap());
+fn test_unknown() {
+    let serde = parse_test(syn::parse_quote!(
+        #[serde(deny_unknown_fields, bound(serialize = "T: Serialize"), crate = "serde", flatten)]
+    ));
+    assert!(serde.flatten);
 }
 
 #[test]
-fn test_rename_all_serialize() {
-    let text = r#"rename_all(serialize = "ser_name")"#;
-    assert_eq!("ser_name", parse_rename_all_serialize(text).unwrap());
-    let text = r#"rename_all(serialize = "ser_name", deserialize = "de_name")"#;
-    assert_eq!("ser_name", parse_rename_all_serialize(text).unwrap());
-    let text = r#"rename_all(deserialize = "de_name", serialize = "ser_name")"#;
-    assert_eq!("ser_name", parse_rename_all_serialize(text).unwrap());
+fn test_field_name() {
+    let ident: Ident = syn::parse_quote!(r#type);
+    let serde = SerdeAttrs::default();
+    assert_eq!("type", serde.field_name(&RenameRule::None, &ident));
+    let ident: Ident = syn::parse_quote!(max_size);
+    assert_eq!("maxSize", serde.field_name(&RenameRule::CamelCase, &ident));
+    let serde = parse_test(syn::parse_quote!(#[serde(rename = "size")]));
+    assert_eq!("size", serde.field_name(&RenameRule::CamelCase, &ident));
 }
@@ -1,3 +1,4 @@
+#![allow(dead_code, clippy::field_reassign_with_default)]
 use serde::Serialize;
 
 #[derive(Debug, Default, Serialize)]
@@ -13,13 +14,13 @@ impl toml_input::TomlInput for TestStruct {
         use std::str::FromStr;
         use toml;
         use toml_input::schema;
-        let default = <TestStruct as Default>::default();
+        let default: TestStruct = <TestStruct as Default>::default();
+        let default_value = toml_input::TomlInput::into_value(default)?;
         let mut table = schema::TableSchema::default();
         let mut meta = schema::Meta::default();
         meta.wrap_type = "".to_string();
         meta.inner_type = "TestStruct".to_string();
-        let raw = toml::Value::try_from(default)?;
-        meta.inner_default = toml_input::PrimValue::new(raw);
+        meta.inner_default = default_value.into_prim();
         meta.defined_docs = " this is comment of struct".to_string();
         table.meta = meta;
         table.fields = Vec::new();
@@ -1,6 +1,4 @@
 use serde::Serialize;
-use strum_macros::{AsRefStr, EnumIter};
-use toml_input_derive::TomlInput;
 
 #[test]
 fn test_struct() {
@@ -21,7 +19,7 @@ fn test_struct() {
 #[test]
 fn test_enum() {
     /// this is comment of enum
-    #[derive(EnumIter, AsRefStr, TomlInput, Debug, Serialize)]
+    #[derive(TomlInput, Debug, Serialize)]
     #[toml_input(enum_style = "expand")]
     #[derive(Default)]
     enum TestEnum {
@@ -0,0 +1,10 @@
+use serde::Serialize;
+use toml_input::TomlInput;
+
+#[derive(TomlInput, Serialize, Default)]
+#[toml_input(bound = "T:: +")]
+struct Wrapper<T> {
+    inner: T,
+}
+
+fn main() {}
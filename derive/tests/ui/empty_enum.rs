@@ -0,0 +1,7 @@
+use serde::Serialize;
+use toml_input::TomlInput;
+
+#[derive(TomlInput, Serialize)]
+enum Never {}
+
+fn main() {}
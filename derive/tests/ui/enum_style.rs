@@ -0,0 +1,12 @@
+use serde::Serialize;
+use toml_input::TomlInput;
+
+#[derive(TomlInput, Serialize, Default)]
+#[toml_input(enum_style = "flod")]
+enum Level {
+    #[default]
+    Info,
+    Warn,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+use toml_input::TomlInput;
+
+#[derive(TomlInput, Serialize, Default)]
+struct Config {
+    #[toml_input(option_style = "skip")]
+    port: Option<u16>,
+}
+
+fn main() {}
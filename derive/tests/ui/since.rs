@@ -0,0 +1,10 @@
+use serde::Serialize;
+use toml_input::TomlInput;
+
+#[derive(TomlInput, Serialize, Default)]
+struct Config {
+    #[toml_input(since = "1.3")]
+    port: u16,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use serde::Serialize;
+use toml_input::TomlInput;
+
+#[derive(TomlInput, Serialize, Default)]
+#[serde(transparent)]
+struct Wrapper {
+    #[serde(skip)]
+    inner: u16,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use toml_input::TomlInput;
+
+#[derive(TomlInput)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
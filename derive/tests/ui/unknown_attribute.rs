@@ -0,0 +1,10 @@
+use serde::Serialize;
+use toml_input::TomlInput;
+
+#[derive(TomlInput, Serialize, Default)]
+struct Config {
+    #[toml_input(inner_defualt = "1")]
+    port: u16,
+}
+
+fn main() {}
@@ -1,9 +1,11 @@
 use crate::{
-    BANG_COMMENT, COMMENT, Error, TomlValue,
+    BANG_COMMENT, COMMENT, Error, MAP_KEY, TomlValue,
     comment::{Comment, CommentType},
-    schema::{Meta, VariantSchema},
+    config::TomlConfig,
+    schema::{Meta, Tagging, VariantSchema},
     util,
     value::BlockValue,
+    yaml::Syntax,
 };
 
 #[derive(Debug, Clone, Default)]
@@ -28,14 +30,119 @@ impl Block {
         !self.is_value()
     }
 
+    pub fn is_template(&self) -> bool {
+        util::is_template_key(&self.key)
+    }
+
     pub fn is_comented(&self) -> bool {
-        self.value.is_none() && self.meta.config.commented
+        self.value.is_none() && (self.selected_config().is_commented() || self.is_template())
+    }
+
+    pub fn instantiate(&mut self, key: &str) {
+        self.key = util::key_fill(&self.key, key);
+        if self.ident == MAP_KEY {
+            self.ident = util::key_ident(&self.key);
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        let Some(value) = &self.value else {
+            return true;
+        };
+        let default = &self.meta.inner_default;
+        if self.is_enum() {
+            value.tag == default.tag
+        } else {
+            default.raw.is_some() && value.value == default.raw
+        }
     }
 
     pub fn is_none_skipped(&self) -> bool {
         self.meta.is_option_type() && self.meta.config.is_none_skipped() && self.value.is_none()
     }
 
+    pub fn selected_tag(&self) -> &str {
+        if let Some(value) = &self.value {
+            &value.tag
+        } else {
+            &self.meta.inner_default.tag
+        }
+    }
+
+    pub fn variant_config(&self, variant: &VariantSchema) -> TomlConfig {
+        let mut config = variant.config.clone();
+        config.merge_parent(&self.meta.config);
+        config
+    }
+
+    pub fn selected_config(&self) -> TomlConfig {
+        match self.variant(self.selected_tag()) {
+            Some(variant) => self.variant_config(variant),
+            None => self.meta.config.clone(),
+        }
+    }
+
+    pub fn variant(&self, tag: &str) -> Option<&VariantSchema> {
+        self.variants
+            .iter()
+            .find(|variant| variant.value.tag == tag)
+    }
+
+    pub fn variant_section_key(&self, tag: &str) -> Option<String> {
+        self.variant(tag)?;
+        Some(util::key_child(&self.key, tag))
+    }
+
+    pub fn variant_content_key(&self, tag: &str) -> String {
+        match &self.meta.tagging {
+            Tagging::External => util::key_child(&self.key, tag),
+            Tagging::Adjacent { content, .. } => util::key_child(&self.key, content),
+            Tagging::Internal { .. } | Tagging::Untagged => self.key.clone(),
+        }
+    }
+
+    pub fn render_variant_tag(&self, tag: &str, commented: bool, selected: bool) -> Vec<String> {
+        self.render_variant_tag_as(Syntax::Toml, BANG_COMMENT, tag, commented, selected)
+    }
+
+    pub(crate) fn render_variant_tag_as(
+        &self,
+        syntax: Syntax,
+        bang: &str,
+        tag: &str,
+        commented: bool,
+        selected: bool,
+    ) -> Vec<String> {
+        let mut lines = Vec::new();
+        let Some(tag_key) = self.meta.tagging.tag_key() else {
+            return lines;
+        };
+        let Some(variant) = self.variant(tag) else {
+            return lines;
+        };
+        let bang = if commented { bang } else { "" };
+        let tag_value = TomlValue::String(tag.to_string());
+        lines.push(format!("{bang}{}", syntax.assign(tag_key, &tag_value)));
+        if variant.is_table() {
+            return lines;
+        }
+        let raw = if !selected {
+            variant.value.raw.clone()
+        } else if let Some(value) = &self.value {
+            value.value.clone()
+        } else {
+            self.meta.inner_default.raw.clone()
+        };
+        if let Some(TomlValue::Table(table)) = raw {
+            for (key, value) in table {
+                if key != tag_key {
+                    lines.push(format!("{bang}{}", syntax.assign(&key, &value)));
+                }
+            }
+        }
+        lines
+    }
+
     pub fn enum_is_expand(&self) -> bool {
         if !self.is_enum() {
             return false;
@@ -53,11 +160,18 @@ impl Block {
     }
 
     pub fn render(&self) -> Result<String, Error> {
+        self.render_as(Syntax::Toml, BANG_COMMENT)
+    }
+
+    pub(crate) fn render_as(&self, syntax: Syntax, bang: &str) -> Result<String, Error> {
         if self.is_none_skipped() {
             return Ok(String::new());
         }
+        if self.meta.tagging.has_tag_key() && !self.meta.is_array {
+            return Ok(String::new());
+        }
         let mut block_value = self.meta.inner_default.clone().flatten();
-        let mut commented = self.meta.config.commented;
+        let mut commented = self.selected_config().is_commented() || self.is_template();
         if let Some(value) = self.value.clone() {
             block_value = value;
             commented = false;
@@ -71,36 +185,41 @@ impl Block {
         let tag = block_value.tag;
         let text;
         if self.enum_is_expand() {
-            text = self.render_enum_expand(commented, tag, raw_value)?;
+            text = self.render_enum_expand(syntax, bang, commented, tag, raw_value)?;
         } else if self.enum_is_fold() {
-            text = self.render_enum_fold(commented, tag, raw_value)?;
+            text = self.render_enum_fold(syntax, bang, commented, tag, raw_value)?;
         } else if self.is_enum() {
-            text = self.render_enum_single(commented, tag, raw_value)?;
+            text = self.render_enum_single(syntax, bang, commented, tag, raw_value)?;
         } else {
-            text = self.render_single(commented, raw_value)?;
+            text = self.render_single(syntax, bang, commented, raw_value)?;
         }
         Ok(text)
     }
 
     fn render_enum_single(
         &self,
+        syntax: Syntax,
+        bang: &str,
         commented: bool,
         tag: String,
         raw_value: TomlValue,
     ) -> Result<String, Error> {
         let mut lines = Vec::new();
+        if let Some(annotation) = self.comment().annotation() {
+            lines.push(annotation);
+        }
         for variant in &self.variants {
-            if variant.value.tag != tag {
+            if variant.value.tag != tag || variant.is_table() {
                 continue;
             }
             let comment = util::comment_lines(&variant.docs);
-            if !self.meta.config.is_comment_hidden() {
+            if !self.variant_config(variant).is_comment_hidden() {
                 lines.push(comment);
             }
             let line = if commented {
-                format!("{}{} = {}", BANG_COMMENT, self.ident, raw_value)
+                format!("{bang}{}", self.assign_variant(syntax, &raw_value))
             } else {
-                format!("{} = {}", self.ident, raw_value)
+                self.assign_variant(syntax, &raw_value)
             };
             lines.push(line);
             break;
@@ -111,6 +230,8 @@ impl Block {
 
     fn render_enum_expand(
         &self,
+        syntax: Syntax,
+        bang: &str,
         commented: bool,
         tag: String,
         raw_value: TomlValue,
@@ -119,20 +240,26 @@ impl Block {
             return Err(Error::EnumStyleError("not enum_expand style".to_string()));
         }
         let mut lines = Vec::new();
+        if let Some(annotation) = self.comment().annotation() {
+            lines.push(annotation);
+        }
         for variant in &self.variants {
+            if variant.is_table() {
+                continue;
+            }
             let comment = util::comment_lines(&variant.docs);
-            if !self.meta.config.is_comment_hidden() {
+            if !self.variant_config(variant).is_comment_hidden() {
                 lines.push(comment);
             }
             if variant.value.tag == tag {
                 let line = if commented {
-                    format!("{}{} = {}", BANG_COMMENT, self.ident, raw_value)
+                    format!("{bang}{}", self.assign_variant(syntax, &raw_value))
                 } else {
-                    format!("{} = {}", self.ident, raw_value)
+                    self.assign_variant(syntax, &raw_value)
                 };
                 lines.push(line);
             } else if let Some(value) = &variant.value.raw {
-                let line = format!("{}{} = {}", BANG_COMMENT, self.ident, value);
+                let line = format!("{bang}{}", self.assign_variant(syntax, value));
                 lines.push(line)
             }
         }
@@ -142,6 +269,8 @@ impl Block {
 
     fn render_enum_fold(
         &self,
+        syntax: Syntax,
+        bang: &str,
         commented: bool,
         tag: String,
         raw_value: TomlValue,
@@ -155,31 +284,63 @@ impl Block {
         if !self.meta.config.is_comment_hidden() {
             lines.push(text);
         }
+        let values_index = lines.len();
         let mut values = Vec::new();
         for variant in &self.variants {
+            if variant.is_table() {
+                continue;
+            }
             if variant.value.tag == tag {
                 let line = if commented {
-                    format!("{}{} = {}", BANG_COMMENT, self.ident, raw_value)
+                    format!("{bang}{}", self.assign_variant(syntax, &raw_value))
                 } else {
-                    format!("{} = {}", self.ident, raw_value)
+                    self.assign_variant(syntax, &raw_value)
                 };
                 lines.push(line);
             }
             if let Some(value) = &variant.value.raw {
-                values.push(format!("{value}"))
+                values.push(self.variant_text(syntax, value))
             }
         }
         if values.len() > 1 {
             lines.insert(
-                1,
-                format!("{} {} = {}", COMMENT, self.ident, values.join(" | ")),
+                values_index,
+                format!(
+                    "{COMMENT} {}",
+                    syntax.assign_text(&self.ident, &values.join(" | "))
+                ),
             );
         }
         lines.retain(|line| !line.trim().is_empty());
         Ok(lines.join("\n"))
     }
 
-    fn render_single(&self, commented: bool, raw_value: TomlValue) -> Result<String, Error> {
+    fn assign_variant(&self, syntax: Syntax, raw: &TomlValue) -> String {
+        syntax.assign_text(&self.ident, &self.variant_text(syntax, raw))
+    }
+
+    // serde_yaml reads the data of an externally tagged variant from a YAML tag,
+    // `mode: !Unix "/s"`, not from a single key mapping
+    fn variant_text(&self, syntax: Syntax, raw: &TomlValue) -> String {
+        if syntax == Syntax::Yaml
+            && self.meta.tagging == Tagging::External
+            && let TomlValue::Table(table) = raw
+            && table.len() == 1
+            && let Some((tag, inner)) = table.iter().next()
+            && self.variant(tag).is_some()
+        {
+            return format!("!{tag} {}", syntax.value(inner));
+        }
+        syntax.value(raw)
+    }
+
+    fn render_single(
+        &self,
+        syntax: Syntax,
+        bang: &str,
+        commented: bool,
+        raw_value: TomlValue,
+    ) -> Result<String, Error> {
         if self.is_enum() {
             panic!()
         }
@@ -190,9 +351,9 @@ impl Block {
             lines.push(text);
         }
         let line = if commented {
-            format!("{}{} = {}", BANG_COMMENT, self.ident, raw_value)
+            format!("{bang}{}", syntax.assign(&self.ident, &raw_value))
         } else {
-            format!("{} = {}", self.ident, raw_value)
+            syntax.assign(&self.ident, &raw_value)
         };
         lines.push(line);
         lines.retain(|line| !line.trim().is_empty());
@@ -201,6 +362,7 @@ impl Block {
 
     pub fn comment(&self) -> Comment {
         let mut comment = self.meta.comment();
+        comment.valued_docs = self.meta.valued_docs_at(&self.key);
         if self.variants.is_empty() {
             comment.comment_type = CommentType::BlockField;
         } else {
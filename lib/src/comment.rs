@@ -1,4 +1,4 @@
-use crate::{PrimValue, config::TomlConfig, error::Error, util};
+use crate::{COMMENT, PrimValue, TomlValue, config::TomlConfig, error::Error, util};
 
 #[derive(Debug, Clone, PartialEq, Default)]
 pub enum CommentType {
@@ -16,7 +16,9 @@ pub struct Comment {
     pub valued_docs: String,
     pub wrap_type: String,
     pub inner_type: String,
+    pub type_name: Option<String>,
     pub inner_default: PrimValue,
+    pub is_array: bool,
     pub comment_type: CommentType,
     pub config: TomlConfig,
 }
@@ -32,6 +34,44 @@ impl Comment {
         } else {
             self.defined_docs.clone()
         };
-        Ok(util::comment_lines(&text))
+        let mut text = util::comment_lines(&text);
+        if self.comment_type != CommentType::Root
+            && let Some(annotation) = self.annotation()
+        {
+            util::append_line(&mut text);
+            text.push_str(&annotation);
+        }
+        Ok(text)
+    }
+
+    // `# type: u32 (optional), default: 8080` or `# type: [string]`, only with
+    // the `Annotate` comment style
+    pub fn annotation(&self) -> Option<String> {
+        if !self.config.is_annotated() {
+            return None;
+        }
+        let name = self.type_name.as_ref().unwrap_or(&self.inner_type);
+        if name.is_empty() {
+            return None;
+        }
+        let mut text = if self.is_array {
+            format!("{COMMENT} type: [{name}]")
+        } else {
+            format!("{COMMENT} type: {name}")
+        };
+        if self.wrap_type == "Option" {
+            text.push_str(" (optional)");
+        }
+        if let Some(raw) = &self.inner_default.raw {
+            let fits = match raw {
+                TomlValue::Table(_) => false,
+                TomlValue::Array(_) => self.is_array,
+                _ => !self.is_array,
+            };
+            if fits {
+                text.push_str(&format!(", default: {raw}"));
+            }
+        }
+        Some(text)
     }
 }
@@ -1,22 +1,13 @@
-#[derive(Debug, Clone, PartialEq)]
+use crate::BANG_COMMENT;
+
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct TomlConfig {
     pub enum_style: Option<EnumStyle>,
     pub option_style: Option<OptionStyle>,
-    pub commented: bool,
+    pub commented: Option<bool>,
     pub comment_style: Option<CommentStyle>,
 }
 
-impl Default for TomlConfig {
-    fn default() -> Self {
-        TomlConfig {
-            enum_style: None,
-            option_style: None,
-            commented: true,
-            comment_style: None,
-        }
-    }
-}
-
 impl TomlConfig {
     pub fn merge_parent(&mut self, parent: &TomlConfig) {
         if self.enum_style.is_none() {
@@ -25,11 +16,18 @@ impl TomlConfig {
         if self.option_style.is_none() {
             self.option_style = parent.option_style;
         }
+        if self.commented.is_none() {
+            self.commented = parent.commented;
+        }
         if self.comment_style.is_none() {
             self.comment_style = parent.comment_style;
         }
     }
 
+    pub fn is_commented(&self) -> bool {
+        self.commented.unwrap_or(true)
+    }
+
     pub fn is_none_skipped(&self) -> bool {
         if let Some(style) = self.option_style {
             style.is_skip_none()
@@ -41,6 +39,10 @@ impl TomlConfig {
     pub fn is_comment_hidden(&self) -> bool {
         matches!(self.comment_style, Some(CommentStyle::Hide))
     }
+
+    pub fn is_annotated(&self) -> bool {
+        matches!(self.comment_style, Some(CommentStyle::Annotate))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -95,4 +97,60 @@ pub enum CommentStyle {
     #[default]
     Show,
     Hide,
+    Annotate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BlankLines {
+    #[default]
+    BetweenSections,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum KeyOrder {
+    #[default]
+    Declared,
+    Sorted,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    pub comment_style: Option<CommentStyle>,
+    pub bang_comment: String,
+    pub blank_lines: BlankLines,
+    pub enum_style: Option<EnumStyle>,
+    pub option_style: Option<OptionStyle>,
+    pub key_order: KeyOrder,
+    pub minimal: bool,
+    pub schema_path: Option<String>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            comment_style: None,
+            bang_comment: BANG_COMMENT.to_string(),
+            blank_lines: BlankLines::default(),
+            enum_style: None,
+            option_style: None,
+            key_order: KeyOrder::default(),
+            minimal: false,
+            schema_path: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn apply(&self, config: &mut TomlConfig) {
+        if let Some(style) = self.comment_style {
+            config.comment_style.get_or_insert(style);
+        }
+        if let Some(style) = self.enum_style {
+            config.enum_style.get_or_insert(style);
+        }
+        if let Some(style) = self.option_style {
+            config.option_style.get_or_insert(style);
+        }
+    }
 }
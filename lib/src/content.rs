@@ -1,12 +1,46 @@
-use crate::{Error, Value, config::CommentStyle, section::Section, util};
+use crate::{
+    BANG_COMMENT, BlockValue, COMMENT, Error, MAP_KEY, SCHEMA_DIRECTIVE, Schema, TAG, TomlValue,
+    UNKNOWN_KEYS, Value,
+    block::Block,
+    config::{BlankLines, CommentStyle, EnumStyle, KeyOrder, RenderOptions, TomlConfig},
+    section::Section,
+    util,
+};
 
 #[derive(Debug, Clone)]
 pub struct TomlContent {
     pub sections: Vec<Section>,
+    pub unknown: Vec<(String, TomlValue)>,
 }
 
 impl TomlContent {
-    pub fn merge_value(&mut self, value: Value) {
+    pub fn new(sections: Vec<Section>) -> Self {
+        TomlContent {
+            sections,
+            unknown: Vec::new(),
+        }
+    }
+
+    // reads the active values only, commented alternatives are plain TOML comments
+    // whatever `bang_comment` they use and come back from the schema on merge, keys
+    // the schema does not know are kept as extra fields of their table
+    pub fn parse(schema: Schema, text: &str) -> Result<Value, Error> {
+        let table: toml::Table = text.parse()?;
+        schema.value_from(TomlValue::Table(table))
+    }
+
+    // values without a block are added to their section when it exists, otherwise
+    // they end up in the trailing unrecognized keys block
+    pub fn merge_value(&mut self, value: Value) -> Vec<String> {
+        self.merge(value, true)
+    }
+
+    // like `merge_value`, but every unmatched value goes to the trailing block
+    pub(crate) fn merge_value_trailing(&mut self, value: Value) -> Vec<String> {
+        self.merge(value, false)
+    }
+
+    fn merge(&mut self, value: Value, in_section: bool) -> Vec<String> {
         let values = value.flatten();
         for value in &values {
             if value.array_index.is_some() {
@@ -31,26 +65,118 @@ impl TomlContent {
                 }
             }
         }
-        for value in values {
-            'f0: for section in &mut self.sections {
+        let mut templates = Vec::new();
+        for value in &values {
+            if value.value.is_none() {
+                continue;
+            }
+            while let Some(template) = self.instantiate_template(value) {
+                if !templates.contains(&template) {
+                    templates.push(template);
+                }
+            }
+        }
+        let mut unmatched = Vec::new();
+        'f0: for value in values {
+            if value.value.is_none() {
+                continue;
+            }
+            for section in &mut self.sections {
                 if section.array_index != value.array_index {
                     continue;
                 }
                 for block in &mut section.blocks {
-                    if block.key == value.key && value.value.is_some() {
+                    if block.key == value.key {
                         block.value = Some(value);
-                        break 'f0;
+                        continue 'f0;
                     }
                 }
             }
+            unmatched.push(value);
+        }
+        for section in &mut self.sections {
+            section
+                .blocks
+                .retain(|block| !templates.contains(&block.key));
+        }
+        self.sections
+            .retain(|section| !templates.contains(&section.key));
+        let mut warnings = Vec::new();
+        for value in unmatched {
+            warnings.push(self.keep_unknown(value, in_section));
+        }
+        warnings
+    }
+
+    fn keep_unknown(&mut self, value: BlockValue, in_section: bool) -> String {
+        let section_key = util::key_parent(&value.key);
+        let section = self.sections.iter_mut().find(|section| {
+            in_section
+                && section.key == section_key
+                && section.array_index == value.array_index
+                && section.variant_tag.is_none()
+                && !section.is_template()
+        });
+        if let Some(section) = section {
+            let warning = format!("unrecognized key `{}` kept in its section", value.key);
+            section.blocks.push(Block {
+                key: value.key.clone(),
+                ident: util::key_ident(&value.key),
+                value: Some(value),
+                ..Default::default()
+            });
+            return warning;
+        }
+        let warning = format!("unrecognized key `{}` moved to `{UNKNOWN_KEYS}`", value.key);
+        if let Some(raw) = value.value {
+            self.unknown.push((value.key, raw));
+        }
+        warning
+    }
+
+    fn instantiate_template(&mut self, value: &BlockValue) -> Option<String> {
+        let mut found: Option<(usize, usize)> = None;
+        let mut found_depth = usize::MAX;
+        for (i, section) in self.sections.iter().enumerate() {
+            if section.array_index != value.array_index {
+                continue;
+            }
+            for (j, block) in section.blocks.iter().enumerate() {
+                if block.key == value.key {
+                    return None;
+                }
+                if !block.is_template() || !util::key_match(&block.key, &value.key) {
+                    continue;
+                }
+                let depth = block.key.matches(MAP_KEY).count();
+                if depth < found_depth {
+                    found = Some((i, j));
+                    found_depth = depth;
+                }
+            }
+        }
+        let (i, j) = found?;
+        let section = &self.sections[i];
+        if section.is_template() {
+            let template = section.key.clone();
+            let mut section = section.clone();
+            section.instantiate(&value.key);
+            self.sections.insert(i, section);
+            Some(template)
+        } else {
+            let template = section.blocks[j].key.clone();
+            let mut block = section.blocks[j].clone();
+            block.instantiate(&value.key);
+            self.sections[i].blocks.insert(j, block);
+            Some(template)
         }
     }
 
     pub fn config_commented(&mut self, commented: bool) {
         for section in &mut self.sections {
-            section.meta.config.commented = commented;
+            section.meta.config.commented = Some(commented);
             for block in &mut section.blocks {
-                block.meta.config.commented = commented;
+                block.meta.config.commented = Some(commented);
             }
         }
     }
@@ -65,14 +191,203 @@ impl TomlContent {
         }
     }
 
+    // defaults used by the render entry points, a `commented` attribute wins
+    pub(crate) fn fill_commented(&mut self, commented: bool) {
+        for section in &mut self.sections {
+            section.meta.config.commented.get_or_insert(commented);
+            for block in &mut section.blocks {
+                block.meta.config.commented.get_or_insert(commented);
+            }
+        }
+    }
+
+    pub(crate) fn fill_commented_optional(&mut self) {
+        for section in &mut self.sections {
+            let optional = section.meta.is_option_type();
+            section.meta.config.commented.get_or_insert(optional);
+            for block in &mut section.blocks {
+                let optional = block.meta.is_option_type();
+                block.meta.config.commented.get_or_insert(optional);
+            }
+        }
+    }
+
+    pub fn config_flex_limit(&mut self, limit: usize) {
+        // a style or limit set by an attribute wins over the render-time limit
+        let flex = |config: &mut TomlConfig| {
+            config.enum_style.get_or_insert(EnumStyle::Flex(limit));
+        };
+        for section in &mut self.sections {
+            flex(&mut section.meta.config);
+            for block in &mut section.blocks {
+                flex(&mut block.meta.config);
+            }
+        }
+    }
+
+    pub fn config_options(&mut self, options: &RenderOptions) {
+        for section in &mut self.sections {
+            options.apply(&mut section.meta.config);
+            for block in &mut section.blocks {
+                options.apply(&mut block.meta.config);
+            }
+            if options.key_order == KeyOrder::Sorted {
+                section.blocks.sort_by(|a, b| a.ident.cmp(&b.ident));
+            }
+        }
+    }
+
+    // array items, map entries and options holding a value are kept whole, their
+    // defaults say nothing about whether the entry itself exists
+    pub fn minimal(&mut self) {
+        let mut whole = Vec::new();
+        let mut kept: Vec<Vec<bool>> = Vec::new();
+        for section in &self.sections {
+            let has_value = section.blocks.iter().any(|block| block.value.is_some());
+            whole.push(
+                section.array_index.is_some()
+                    || section.meta.is_map()
+                    || (section.meta.is_option_type() && has_value),
+            );
+            let blocks = section.blocks.iter().map(|block| {
+                block.value.is_some()
+                    && (block.meta.is_map() || block.meta.is_option_type() || !block.is_default())
+            });
+            kept.push(blocks.collect());
+        }
+        // an enum left at its default tag still has to select a variant whose
+        // fields were changed, nested variants come later so walk backwards
+        for i in (0..self.sections.len()).rev() {
+            for j in 0..self.sections[i].blocks.len() {
+                let block = &self.sections[i].blocks[j];
+                if kept[i][j] || block.value.is_none() {
+                    continue;
+                }
+                let Some(key) = block.variant_section_key(block.selected_tag()) else {
+                    continue;
+                };
+                let array_index = self.sections[i].array_index;
+                let selected = self.sections.iter().enumerate().any(|(k, section)| {
+                    section.array_index == array_index
+                        && (section.key == key || section.key.starts_with(&format!("{key}{TAG}")))
+                        && kept[k].contains(&true)
+                });
+                kept[i][j] = selected;
+            }
+        }
+        // the sections below a whole one and the variant selected by a kept enum
+        // are needed to read it back, parents come first so walk forwards
+        for i in 0..self.sections.len() {
+            let section = &self.sections[i];
+            let mut below = Vec::new();
+            if whole[i] {
+                kept[i] = section
+                    .blocks
+                    .iter()
+                    .map(|block| block.value.is_some())
+                    .collect();
+                below.push(section.key.clone());
+            }
+            for (block, kept) in section.blocks.iter().zip(&kept[i]) {
+                if *kept && let Some(key) = block.variant_section_key(block.selected_tag()) {
+                    below.push(key);
+                }
+            }
+            for (k, inner) in self.sections.iter().enumerate().skip(i + 1) {
+                let is_below = below
+                    .iter()
+                    .any(|key| inner.key == *key || inner.key.starts_with(&format!("{key}{TAG}")));
+                if is_below && inner.array_index == section.array_index {
+                    whole[k] = true;
+                }
+            }
+        }
+        for (section, kept) in self.sections.iter_mut().zip(kept) {
+            let mut kept = kept.into_iter();
+            section.blocks.retain(|_| kept.next().unwrap_or(false));
+        }
+        self.sections.retain(|section| !section.blocks.is_empty());
+    }
+
     pub fn render(&self) -> Result<String, Error> {
+        let mut sections = self.render_sections(BANG_COMMENT)?;
+        if !self.unknown.is_empty() {
+            sections.push(TomlContent::render_unknown(&self.unknown));
+        }
+        Ok(sections.join("\n\n").trim().to_string())
+    }
+
+    pub fn render_with(&self, options: &RenderOptions) -> Result<String, Error> {
+        let mut content = self.clone();
+        content.config_options(options);
+        if options.minimal {
+            content.minimal();
+        }
+        let bang = options.bang_comment.as_str();
+        let mut sections = content.render_sections(bang)?;
+        if !content.unknown.is_empty() {
+            sections.push(TomlContent::render_unknown_marked(&content.unknown, bang));
+        }
+        let separator = match options.blank_lines {
+            BlankLines::BetweenSections => "\n\n",
+            BlankLines::None => "\n",
+        };
+        let mut lines = Vec::new();
+        if let Some(path) = &options.schema_path {
+            lines.push(format!("{SCHEMA_DIRECTIVE} {path}"));
+            lines.push(String::new());
+        }
+        lines.push(sections.join(separator).trim().to_string());
+        Ok(lines.join("\n"))
+    }
+
+    pub fn render_unknown(unknown: &[(String, TomlValue)]) -> String {
+        TomlContent::render_unknown_marked(unknown, BANG_COMMENT)
+    }
+
+    fn render_unknown_marked(unknown: &[(String, TomlValue)], bang: &str) -> String {
+        let mut lines = vec![format!("{COMMENT} {UNKNOWN_KEYS}")];
+        for (key, value) in unknown {
+            lines.push(format!("{bang}{key} = {value}"));
+        }
+        lines.join("\n")
+    }
+
+    fn render_sections(&self, bang: &str) -> Result<Vec<String>, Error> {
         let mut lines = Vec::new();
         for section in &self.sections {
-            let line = section.render()?;
+            let line = match self.variant_block(section) {
+                Some(block) => {
+                    let selected = section.variant_tag.as_deref() == Some(block.selected_tag());
+                    if !selected && !block.enum_is_expand() {
+                        continue;
+                    }
+                    section.render_variant_marked(block, selected, bang)?
+                }
+                None => section.render_marked(bang)?,
+            };
             if !line.trim().is_empty() {
                 lines.push(line);
             }
         }
-        Ok(lines.join("\n\n").trim().to_string())
+        Ok(lines)
+    }
+
+    pub(crate) fn variant_block(&self, section: &Section) -> Option<&Block> {
+        let tag = section.variant_tag.as_ref()?;
+        for parent in &self.sections {
+            if parent.array_index != section.array_index {
+                continue;
+            }
+            for block in &parent.blocks {
+                let Some(key) = block.variant_section_key(tag) else {
+                    continue;
+                };
+                if section.key == key || section.key.starts_with(&format!("{key}{TAG}")) {
+                    return Some(block);
+                }
+            }
+        }
+        None
     }
 }
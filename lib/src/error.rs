@@ -11,4 +11,10 @@ pub enum Error {
     EnumEmpty,
     #[error("enum style error: {0}")]
     EnumStyleError(String),
+    #[error("parsing toml failed: {0}")]
+    TomlDeError(#[from] toml::de::Error),
+    #[error("parsing toml failed: {0}")]
+    TomlEditError(#[from] toml_edit::TomlError),
+    #[error("value does not match schema: {0}")]
+    ValueTypeError(String),
 }
@@ -0,0 +1,366 @@
+use serde_json::{Map, Value as JsonValue, json};
+
+use crate::{
+    Schema, TAG, TomlValue,
+    markdown::{anchor, table_heading},
+    schema::{FieldSchema, Meta, PrimSchema, TableSchema, Tagging, VariantSchema},
+    util,
+};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+impl Schema {
+    pub fn to_json_schema(&self) -> JsonValue {
+        Exporter::default().export(self)
+    }
+
+    // `docs_url` points at the page generated by `to_markdown`
+    pub fn to_taplo_schema(&self, docs_url: Option<&str>) -> JsonValue {
+        let exporter = Exporter {
+            taplo: true,
+            docs_url: docs_url.map(str::to_string),
+        };
+        exporter.export(self)
+    }
+}
+
+#[derive(Default)]
+struct Exporter {
+    taplo: bool,
+    docs_url: Option<String>,
+}
+
+// where a property lives in the markdown reference, `None` inside maps and
+// variants which have no single heading
+#[derive(Clone)]
+struct Location {
+    key: String,
+    anchor: String,
+}
+
+impl Exporter {
+    fn export(&self, schema: &Schema) -> JsonValue {
+        let mut object = Map::new();
+        object.insert("$schema".to_string(), json!(DRAFT));
+        let meta = schema.meta();
+        if !meta.inner_type.is_empty() {
+            object.insert("title".to_string(), json!(meta.inner_type));
+        }
+        let location = Location {
+            key: String::new(),
+            anchor: anchor(&meta.inner_type),
+        };
+        if let JsonValue::Object(schema) = self.schema_json(schema, "", Some(location)) {
+            object.extend(schema);
+        }
+        JsonValue::Object(object)
+    }
+
+    fn schema_json(&self, schema: &Schema, docs: &str, location: Option<Location>) -> JsonValue {
+        let meta = schema.meta();
+        let mut item = schema.clone();
+        if meta.is_map() {
+            item.meta_mut().map_depth -= 1;
+        } else {
+            item.meta_mut().is_array = false;
+        }
+        let mut object = if meta.is_map() {
+            json!({"type": "object", "additionalProperties": self.schema_json(&item, "", None)})
+        } else if meta.is_array {
+            let location = location.clone().filter(|_| schema.is_table());
+            json!({"type": "array", "items": self.schema_json(&item, "", location)})
+        } else {
+            match schema {
+                Schema::Prim(prim) if prim.variants.is_empty() => prim_json(&prim.meta.inner_type),
+                Schema::Prim(prim) => self.enum_json(prim),
+                Schema::Table(table) => self.table_json(table, location.clone()),
+            }
+        };
+        let docs = if docs.trim().is_empty() {
+            &meta.defined_docs
+        } else {
+            docs
+        };
+        if let JsonValue::Object(object) = &mut object {
+            if let Some(description) = description(docs) {
+                object.insert("description".to_string(), json!(description));
+            }
+            if let Some(default) = schema_default(schema) {
+                object.insert("default".to_string(), default);
+            }
+            if let (Some(url), Some(location)) = (&self.docs_url, &location) {
+                let link = format!("{url}#{}", location.anchor);
+                taplo(object).insert("links".to_string(), json!({"key": link}));
+            }
+        }
+        object
+    }
+
+    fn table_json(&self, table: &TableSchema, location: Option<Location>) -> JsonValue {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        let mut additional = JsonValue::Bool(false);
+        self.collect_fields(
+            table,
+            location,
+            &mut properties,
+            &mut required,
+            &mut additional,
+        );
+        let mut object = Map::new();
+        object.insert("type".to_string(), json!("object"));
+        object.insert("properties".to_string(), JsonValue::Object(properties));
+        if !required.is_empty() {
+            if self.taplo {
+                taplo(&mut object).insert("initKeys".to_string(), json!(required));
+            }
+            object.insert("required".to_string(), json!(required));
+        }
+        object.insert("additionalProperties".to_string(), additional);
+        JsonValue::Object(object)
+    }
+
+    // flattened fields share the parent object, like `TableSchema::take_fields`
+    fn collect_fields(
+        &self,
+        table: &TableSchema,
+        location: Option<Location>,
+        properties: &mut Map<String, JsonValue>,
+        required: &mut Vec<String>,
+        additional: &mut JsonValue,
+    ) {
+        for field in &table.fields {
+            if field.removed {
+                if self.taplo {
+                    properties.insert(field.ident.clone(), hidden(field, None));
+                }
+                continue;
+            }
+            if field.flat {
+                match &field.schema {
+                    Schema::Table(inner) if !inner.meta.is_array && !inner.meta.is_map() => {
+                        let location = location.clone();
+                        self.collect_fields(inner, location, properties, required, additional);
+                    }
+                    Schema::Prim(prim) if prim.meta.is_map() && prim.variants.is_empty() => {
+                        let mut item = field.schema.clone();
+                        item.meta_mut().map_depth -= 1;
+                        *additional = self.schema_json(&item, "", None);
+                    }
+                    _ => *additional = JsonValue::Bool(true),
+                }
+                continue;
+            }
+            let location = location.as_ref().map(|location| {
+                let meta = field.schema.meta();
+                if !field.schema.is_table() || meta.is_map() {
+                    return location.clone();
+                }
+                let key = util::key_child(&location.key, &field.ident);
+                let anchor = anchor(&table_heading(&key, meta.is_array));
+                Location { key, anchor }
+            });
+            let property = self.schema_json(&field.schema, &field.docs, location);
+            for alias in &field.aliases {
+                properties.insert(alias.clone(), property.clone());
+            }
+            properties.insert(field.ident.clone(), property);
+            // serde fills in a missing option or defaulted field
+            if !field.schema.meta().is_option_type() && !field.has_default {
+                required.push(field.ident.clone());
+            }
+            if self.taplo
+                && let Some(old) = &field.renamed_from
+                && !old.contains(TAG)
+                && !properties.contains_key(old)
+            {
+                properties.insert(old.clone(), hidden(field, Some(&field.ident)));
+            }
+        }
+    }
+
+    fn enum_json(&self, prim: &PrimSchema) -> JsonValue {
+        let mut object = if prim.variants.iter().all(|variant| !variant.is_table()) {
+            let values: Vec<_> = prim.variants.iter().map(variant_value).collect();
+            json!({"enum": values})
+        } else {
+            let variants: Vec<_> = prim
+                .variants
+                .iter()
+                .map(|variant| self.variant_json(variant, &prim.meta.tagging))
+                .collect();
+            json!({"oneOf": variants})
+        };
+        if self.taplo
+            && let JsonValue::Object(object) = &mut object
+        {
+            let docs: Vec<_> = prim
+                .variants
+                .iter()
+                .map(|variant| description(&variant.docs).unwrap_or_default())
+                .collect();
+            let mut variant_docs = json!({"enumValues": docs});
+            let default = prim
+                .variants
+                .iter()
+                .find(|variant| variant.value.tag == prim.meta.inner_default.tag)
+                .and_then(|variant| description(&variant.docs));
+            if let Some(default) = default {
+                variant_docs["defaultValue"] = json!(default);
+            }
+            taplo(object).insert("docs".to_string(), variant_docs);
+        }
+        object
+    }
+
+    fn variant_json(&self, variant: &VariantSchema, tagging: &Tagging) -> JsonValue {
+        let tag = &variant.value.tag;
+        let table = match &variant.schema {
+            Some(schema) => self.table_json(schema, None),
+            None => json!({"type": "object", "properties": {}, "additionalProperties": false}),
+        };
+        let mut object = match tagging {
+            Tagging::External if !variant.is_table() => json!({"const": variant_value(variant)}),
+            Tagging::External => json!({
+                "type": "object",
+                "properties": {tag: table},
+                "required": [tag],
+                "additionalProperties": false,
+            }),
+            Tagging::Internal { tag: key } => {
+                let mut table = table;
+                table["properties"][key] = json!({"const": tag});
+                let mut required = vec![json!(key)];
+                if let Some(JsonValue::Array(fields)) = table.get("required") {
+                    required.extend(fields.iter().cloned());
+                }
+                table["required"] = JsonValue::Array(required);
+                table
+            }
+            Tagging::Adjacent { tag: key, content } if variant.is_table() => json!({
+                "type": "object",
+                "properties": {key: {"const": tag}, content: table},
+                "required": [key, content],
+                "additionalProperties": false,
+            }),
+            Tagging::Adjacent { tag: key, .. } => json!({
+                "type": "object",
+                "properties": {key: {"const": tag}},
+                "required": [key],
+                "additionalProperties": false,
+            }),
+            Tagging::Untagged if !variant.is_table() => json!({"const": variant_value(variant)}),
+            Tagging::Untagged => table,
+        };
+        if let Some(description) = description(&variant.docs) {
+            object["description"] = json!(description);
+        }
+        object
+    }
+}
+
+fn taplo(object: &mut Map<String, JsonValue>) -> &mut Map<String, JsonValue> {
+    let extension = object
+        .entry("x-taplo")
+        .or_insert_with(|| JsonValue::Object(Map::new()));
+    match extension {
+        JsonValue::Object(extension) => extension,
+        _ => unreachable!("x-taplo is always inserted as an object"),
+    }
+}
+
+// old keys stay valid for editors but are not offered for completion
+fn hidden(field: &FieldSchema, renamed_to: Option<&str>) -> JsonValue {
+    let mut description = match renamed_to {
+        Some(new) => format!("renamed to `{new}`"),
+        None => "removed".to_string(),
+    };
+    if let Some(since) = &field.since {
+        description.push_str(&format!(" in {since}"));
+    }
+    json!({
+        "description": description,
+        "deprecated": true,
+        "x-taplo": {"hidden": true},
+    })
+}
+
+fn prim_json(inner_type: &str) -> JsonValue {
+    match inner_type {
+        "bool" => json!({"type": "boolean"}),
+        "string" | "path" => json!({"type": "string"}),
+        "f32" | "f64" => json!({"type": "number"}),
+        "i8" | "i16" | "i32" | "i64" | "isize" => json!({"type": "integer"}),
+        "u8" | "u16" | "u32" | "u64" | "usize" => json!({"type": "integer", "minimum": 0}),
+        _ => json!({}),
+    }
+}
+
+fn variant_value(variant: &VariantSchema) -> JsonValue {
+    match &variant.value.raw {
+        Some(raw) => toml_to_json(raw),
+        None => json!(variant.value.tag),
+    }
+}
+
+// a default of the wrong shape is the item type's default, not the field's,
+// and a map default can't be told apart from its item default
+fn default_json(meta: &Meta) -> Option<JsonValue> {
+    if meta.is_option_type() || meta.is_map() {
+        return None;
+    }
+    let raw = meta.inner_default.raw.as_ref()?;
+    let fits = match raw {
+        TomlValue::Array(_) => meta.is_array,
+        _ => !meta.is_array,
+    };
+    fits.then(|| toml_to_json(raw))
+}
+
+// a table's default takes the defaults of its fields, those come from serde's
+// `default` attributes and can differ from the table's `Default` impl
+fn schema_default(schema: &Schema) -> Option<JsonValue> {
+    let mut default = default_json(schema.meta())?;
+    if let (Schema::Table(table), JsonValue::Object(object)) = (schema, &mut default) {
+        field_defaults(table, object);
+    }
+    Some(default)
+}
+
+fn field_defaults(table: &TableSchema, object: &mut Map<String, JsonValue>) {
+    for field in table.fields.iter().filter(|field| !field.removed) {
+        match &field.schema {
+            Schema::Table(inner) if field.flat => field_defaults(inner, object),
+            schema if !field.flat => {
+                if let Some(default) = schema_default(schema) {
+                    object.insert(field.ident.clone(), default);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn description(docs: &str) -> Option<String> {
+    let lines: Vec<_> = docs.lines().map(str::trim).collect();
+    let text = lines.join("\n").trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn toml_to_json(raw: &TomlValue) -> JsonValue {
+    match raw {
+        TomlValue::String(text) => json!(text),
+        TomlValue::Integer(number) => json!(number),
+        TomlValue::Float(number) => json!(number),
+        TomlValue::Boolean(flag) => json!(flag),
+        TomlValue::Datetime(datetime) => json!(datetime.to_string()),
+        TomlValue::Array(values) => JsonValue::Array(values.iter().map(toml_to_json).collect()),
+        TomlValue::Table(table) => {
+            let object = table
+                .iter()
+                .map(|(key, value)| (key.clone(), toml_to_json(value)))
+                .collect();
+            JsonValue::Object(object)
+        }
+    }
+}
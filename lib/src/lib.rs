@@ -2,12 +2,18 @@ pub mod block;
 pub mod comment;
 pub mod config;
 pub mod error;
+#[cfg(feature = "json-schema")]
+mod json_schema;
+mod markdown;
+pub mod migrate;
 pub mod schema;
 pub mod section;
 mod toml_input;
 pub use toml_input::*;
 pub mod util;
+pub mod validate;
 mod value;
+mod yaml;
 pub use value::*;
 mod content;
 pub use content::*;
@@ -18,7 +24,10 @@ pub use toml_input_derive::TomlInput;
 
 const TAG: &str = ".";
 const ROOT_KEY: &str = "";
+const MAP_KEY: &str = "<name>";
 const COMMENT: &str = "#";
 const BANG_COMMENT: &str = "#!";
+const UNKNOWN_KEYS: &str = "Unrecognized keys";
+const SCHEMA_DIRECTIVE: &str = "#:schema";
 
 use toml::Value as TomlValue;
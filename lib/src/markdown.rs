@@ -0,0 +1,181 @@
+use crate::{
+    MAP_KEY, ROOT_KEY, Schema, TAG,
+    block::Block,
+    schema::{Meta, VariantSchema},
+    section::Section,
+    util,
+};
+
+impl Schema {
+    pub fn to_markdown(&self) -> String {
+        let title = self.meta().inner_type.clone();
+        let mut sections = self.clone().flatten();
+        sections.retain(|section| section.variant_tag.is_none() || !section.blocks.is_empty());
+        let headings: Vec<_> = sections
+            .iter()
+            .map(|section| heading(&sections, section))
+            .collect();
+        let mut parts = Vec::new();
+        for (section, heading) in sections.iter().zip(&headings) {
+            let mut lines = Vec::new();
+            if section.key == ROOT_KEY {
+                lines.push(format!("# {title}"));
+            } else {
+                lines.push(format!("## {heading}"));
+            }
+            let docs = docs(&section.meta, &section.key);
+            if !docs.is_empty() {
+                lines.push(String::new());
+                lines.push(docs.join("\n"));
+            }
+            let mut rows = Vec::new();
+            for block in &section.blocks {
+                rows.push(block_row(&sections, &headings, block));
+            }
+            for (child, child_heading) in sections.iter().zip(&headings) {
+                if child.variant_tag.is_none()
+                    && child.key != ROOT_KEY
+                    && util::key_parent(&field_key(&child.key)) == section.key
+                {
+                    rows.push(section_row(child, child_heading));
+                }
+            }
+            if !rows.is_empty() {
+                lines.push(String::new());
+                lines.push("| Key | Type | Default | Allowed values | Description |".to_string());
+                lines.push("| --- | --- | --- | --- | --- |".to_string());
+                lines.append(&mut rows);
+            }
+            parts.push(lines.join("\n"));
+        }
+        parts.join("\n\n")
+    }
+}
+
+pub(crate) fn table_heading(key: &str, is_array: bool) -> String {
+    if is_array {
+        format!("`[[{key}]]`")
+    } else {
+        format!("`[{key}]`")
+    }
+}
+
+fn heading(sections: &[Section], section: &Section) -> String {
+    let Some(tag) = &section.variant_tag else {
+        return table_heading(&section.key, section.meta.is_array);
+    };
+    let block = sections
+        .iter()
+        .flat_map(|parent| &parent.blocks)
+        .find(|block| block.variant_section_key(tag).as_ref() == Some(&section.key));
+    let key = match block {
+        Some(block) => block.variant_content_key(tag),
+        None => section.key.clone(),
+    };
+    format!("{} ({tag})", table_heading(&key, section.meta.is_array))
+}
+
+// GitHub drops punctuation from heading anchors, keys like `a.b` become `ab`
+pub(crate) fn anchor(heading: &str) -> String {
+    let mut anchor = String::new();
+    for c in heading.trim().to_lowercase().chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            anchor.push(c);
+        } else if c == ' ' {
+            anchor.push('-');
+        }
+    }
+    anchor
+}
+
+fn block_row(sections: &[Section], headings: &[String], block: &Block) -> String {
+    let allowed: Vec<_> = block
+        .variants
+        .iter()
+        .map(|variant| variant_link(sections, headings, block, variant))
+        .collect();
+    let default = match &block.meta.inner_default.raw {
+        Some(raw) if !block.meta.is_option_type() => format!("`{raw}`"),
+        _ => String::new(),
+    };
+    row(&[
+        format!("`{}`", block.ident),
+        type_name(&block.meta),
+        default,
+        allowed.join(", "),
+        docs(&block.meta, &block.key).join("<br>"),
+    ])
+}
+
+fn section_row(section: &Section, heading: &str) -> String {
+    let ident = util::key_ident(&field_key(&section.key));
+    row(&[
+        format!("[`{ident}`](#{})", anchor(heading)),
+        type_name(&section.meta),
+        String::new(),
+        String::new(),
+        docs(&section.meta, &section.key).join("<br>"),
+    ])
+}
+
+fn variant_link(
+    sections: &[Section],
+    headings: &[String],
+    block: &Block,
+    variant: &VariantSchema,
+) -> String {
+    let value = match &variant.value.raw {
+        Some(raw) if !variant.is_table() => format!("`{raw}`"),
+        _ => format!("`{}`", variant.value.tag),
+    };
+    let Some(key) = block.variant_section_key(&variant.value.tag) else {
+        return value;
+    };
+    let found = sections
+        .iter()
+        .zip(headings)
+        .find(|(section, _)| section.key == key && !section.blocks.is_empty());
+    match found {
+        Some((_, heading)) => format!("[{value}](#{})", anchor(heading)),
+        None => value,
+    }
+}
+
+fn type_name(meta: &Meta) -> String {
+    let name = meta.type_name.as_ref().unwrap_or(&meta.inner_type);
+    if name.is_empty() {
+        return String::new();
+    }
+    if meta.wrap_type.is_empty() {
+        format!("`{name}`")
+    } else {
+        format!("`{}<{name}>`", meta.wrap_type)
+    }
+}
+
+// a map section like `[servers.<name>]` is listed as the `servers` field
+fn field_key(key: &str) -> String {
+    let mut idents = util::key_split(key);
+    while idents.len() > 1 && idents.last() == Some(&MAP_KEY) {
+        idents.pop();
+    }
+    idents.join(TAG)
+}
+
+fn docs(meta: &Meta, key: &str) -> Vec<String> {
+    let valued_docs = meta.valued_docs_at(key);
+    let docs = if valued_docs.is_empty() {
+        &meta.defined_docs
+    } else {
+        &valued_docs
+    };
+    docs.lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn row(cells: &[String]) -> String {
+    let cells: Vec<_> = cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+    format!("| {} |", cells.join(" | "))
+}
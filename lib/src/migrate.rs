@@ -0,0 +1,272 @@
+use std::fmt;
+
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value as EditValue};
+
+use crate::{
+    Error, Schema, TAG,
+    schema::{FieldSchema, TableSchema},
+    util,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Renamed {
+        from: String,
+        to: String,
+        since: Option<String>,
+    },
+    Removed {
+        key: String,
+        since: Option<String>,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let since = match self {
+            Change::Renamed { from, to, since } => {
+                write!(f, "renamed `{from}` to `{to}`")?;
+                since
+            }
+            Change::Removed { key, since } => {
+                write!(f, "removed `{key}`")?;
+                since
+            }
+        };
+        if let Some(since) = since {
+            write!(f, " (since {since})")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    pub text: String,
+    pub changes: Vec<Change>,
+}
+
+impl MigrationReport {
+    pub fn is_changed(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<_> = self.changes.iter().map(Change::to_string).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Schema {
+    // `renamed_from` is relative to the field's table, a leading `.` makes it
+    // relative to the document root so a key can move between sections
+    pub fn migrate(&self, text: &str) -> Result<MigrationReport, Error> {
+        let mut document: DocumentMut = text.parse()?;
+        let mut changes = Vec::new();
+        if let Schema::Table(schema) = self {
+            let mut moves = Vec::new();
+            root_moves(schema, "", &mut moves);
+            for (from, field, to) in moves {
+                let root = document.as_table_mut() as &mut dyn TableLike;
+                if move_item(root, &from, &to) {
+                    changes.push(Change::Renamed {
+                        from,
+                        to,
+                        since: field.since.clone(),
+                    });
+                }
+            }
+            migrate_table(schema, document.as_table_mut(), "", &mut changes);
+        }
+        Ok(MigrationReport {
+            text: document.to_string(),
+            changes,
+        })
+    }
+}
+
+fn root_moves<'a>(
+    schema: &'a TableSchema,
+    key: &str,
+    moves: &mut Vec<(String, &'a FieldSchema, String)>,
+) {
+    for field in &schema.fields {
+        let field_key = if field.flat {
+            key.to_string()
+        } else {
+            util::key_child(key, &field.ident)
+        };
+        if let Some(old) = &field.renamed_from
+            && old.starts_with(TAG)
+        {
+            moves.push((util::remove_prefix_tag(old), field, field_key.clone()));
+        }
+        if let Schema::Table(inner) = &field.schema
+            && !inner.meta.is_array
+            && !inner.meta.is_map()
+        {
+            root_moves(inner, &field_key, moves);
+        }
+    }
+}
+
+fn migrate_table(
+    schema: &TableSchema,
+    table: &mut dyn TableLike,
+    key: &str,
+    changes: &mut Vec<Change>,
+) {
+    for field in &schema.fields {
+        let since = field.since.clone();
+        if field.removed {
+            if table.remove(&field.ident).is_some() {
+                let key = util::key_child(key, &field.ident);
+                changes.push(Change::Removed { key, since });
+            }
+            continue;
+        }
+        let Some(old) = &field.renamed_from else {
+            continue;
+        };
+        if !old.starts_with(TAG) && move_item(table, old, &util::key_quote(&field.ident)) {
+            changes.push(Change::Renamed {
+                from: util::key_join(key, old),
+                to: util::key_child(key, &field.ident),
+                since,
+            });
+        }
+    }
+    for field in schema.fields.iter().filter(|field| !field.removed) {
+        if field.flat {
+            if let Schema::Table(inner) = &field.schema
+                && !inner.meta.is_array
+                && !inner.meta.is_map()
+            {
+                migrate_table(inner, table, key, changes);
+            }
+            continue;
+        }
+        if let Some(item) = table.get_mut(&field.ident) {
+            let field_key = util::key_child(key, &field.ident);
+            migrate_item(&field.schema, item, &field_key, changes);
+        }
+    }
+}
+
+fn migrate_item(schema: &Schema, item: &mut Item, key: &str, changes: &mut Vec<Change>) {
+    if schema.meta().is_map() {
+        let Some(table) = item.as_table_like_mut() else {
+            return;
+        };
+        let mut inner = schema.clone();
+        inner.meta_mut().map_depth -= 1;
+        let idents: Vec<_> = table.iter().map(|(ident, _)| ident.to_string()).collect();
+        for ident in idents {
+            if let Some(item) = table.get_mut(&ident) {
+                migrate_item(&inner, item, &util::key_child(key, &ident), changes);
+            }
+        }
+        return;
+    }
+    let Schema::Table(schema) = schema else {
+        return;
+    };
+    if !schema.meta.is_array {
+        if let Some(table) = item.as_table_like_mut() {
+            migrate_table(schema, table, key, changes);
+        }
+        return;
+    }
+    match item {
+        Item::ArrayOfTables(array) => {
+            for table in array.iter_mut() {
+                migrate_table(schema, table, key, changes);
+            }
+        }
+        Item::Value(EditValue::Array(array)) => {
+            for value in array.iter_mut() {
+                if let Some(table) = value.as_inline_table_mut() {
+                    migrate_table(schema, table, key, changes);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// never overwrites a key the user already set under the new name
+fn move_item(table: &mut dyn TableLike, from: &str, to: &str) -> bool {
+    let from: Vec<_> = util::key_split(from)
+        .into_iter()
+        .map(util::key_unquote)
+        .collect();
+    let to: Vec<_> = util::key_split(to)
+        .into_iter()
+        .map(util::key_unquote)
+        .collect();
+    if lookup(table, &to).is_some() || !can_insert(table, &to) {
+        return false;
+    }
+    let Some((last, parent)) = from.split_last() else {
+        return false;
+    };
+    let Some(parent_table) = lookup_table(table, parent) else {
+        return false;
+    };
+    let decor = parent_table.key(last).map(|key| key.leaf_decor().clone());
+    let Some(item) = parent_table.remove(last) else {
+        return false;
+    };
+    let (last, parent) = to.split_last().expect("split yields at least one key");
+    let mut target = table;
+    for ident in parent {
+        let mut implicit = Table::new();
+        implicit.set_implicit(true);
+        let entry = target.entry(ident).or_insert(Item::Table(implicit));
+        let Some(next) = entry.as_table_like_mut() else {
+            return false;
+        };
+        target = next;
+    }
+    target.insert(last, item);
+    if let (Some(decor), Some(mut key)) = (decor, target.key_mut(last)) {
+        *key.leaf_decor_mut() = decor;
+    }
+    true
+}
+
+fn lookup<'a>(table: &'a dyn TableLike, path: &[String]) -> Option<&'a Item> {
+    let (last, parent) = path.split_last()?;
+    let mut current = table;
+    for ident in parent {
+        current = current.get(ident)?.as_table_like()?;
+    }
+    current.get(last)
+}
+
+fn lookup_table<'a>(
+    table: &'a mut dyn TableLike,
+    path: &[String],
+) -> Option<&'a mut dyn TableLike> {
+    let mut current = table;
+    for ident in path {
+        current = current.get_mut(ident)?.as_table_like_mut()?;
+    }
+    Some(current)
+}
+
+fn can_insert(table: &dyn TableLike, path: &[String]) -> bool {
+    let mut current = table;
+    for ident in &path[..path.len() - 1] {
+        let Some(item) = current.get(ident) else {
+            return true;
+        };
+        let Some(next) = item.as_table_like() else {
+            return false;
+        };
+        current = next;
+    }
+    true
+}
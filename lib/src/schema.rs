@@ -1,9 +1,11 @@
-use crate::TomlValue;
 use crate::comment::Comment;
 use crate::config::TomlConfig;
+use crate::error::Error;
 use crate::util;
-use crate::value::PrimValue;
+use crate::value::{ArrayValue, FieldValue, PrimValue, TableValue, Value, VariantValue};
+use crate::{MAP_KEY, TAG, TomlValue};
 use crate::{block::Block, section::Section};
+use toml::map::Map;
 
 #[derive(Debug, Clone, Default)]
 pub struct Meta {
@@ -12,8 +14,13 @@ pub struct Meta {
     pub valued_docs: String,
     pub wrap_type: String,
     pub inner_type: String,
+    pub type_name: Option<String>,
     pub inner_default: PrimValue,
     pub is_array: bool,
+    pub map_depth: usize,
+    pub tagging: Tagging,
+    pub renamed_from: Option<String>,
+    pub since: Option<String>,
 }
 
 impl Meta {
@@ -22,22 +29,111 @@ impl Meta {
             config: self.config.clone(),
             defined_docs: self.defined_docs.clone(),
             valued_docs: self.valued_docs.clone(),
+            wrap_type: self.wrap_type.clone(),
             inner_type: self.inner_type.clone(),
+            type_name: self.type_name.clone(),
             inner_default: self.inner_default.clone(),
+            is_array: self.is_array,
             ..Default::default()
         }
     }
 
+    pub fn is_map(&self) -> bool {
+        self.map_depth > 0
+    }
+
+    // the docs with a `renamed from` note, a relative old name is resolved against
+    // the table of the field rendered at `key`
+    pub fn valued_docs_at(&self, key: &str) -> String {
+        let mut docs = self.valued_docs.clone();
+        let Some(old) = &self.renamed_from else {
+            return docs;
+        };
+        let path = if old.starts_with(TAG) {
+            old.clone()
+        } else {
+            let mut idents = util::key_split(key);
+            idents.truncate(idents.len().saturating_sub(self.map_depth + 1));
+            util::key_join(&idents.join(TAG), old)
+        };
+        util::append_line(&mut docs);
+        docs.push_str(&format!(" renamed from `{path}`"));
+        if let Some(since) = &self.since {
+            docs.push_str(&format!(" in {since}"));
+        }
+        docs
+    }
+
     pub fn is_option_type(&self) -> bool {
         self.wrap_type == "Option"
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Tagging {
+    #[default]
+    External,
+    Internal {
+        tag: String,
+    },
+    Adjacent {
+        tag: String,
+        content: String,
+    },
+    Untagged,
+}
+
+impl Tagging {
+    pub fn tag_key(&self) -> Option<&str> {
+        match self {
+            Tagging::Internal { tag } | Tagging::Adjacent { tag, .. } => Some(tag),
+            _ => None,
+        }
+    }
+
+    pub fn has_tag_key(&self) -> bool {
+        self.tag_key().is_some()
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct VariantSchema {
     pub docs: String,
     pub value: PrimValue,
     pub config: TomlConfig,
+    pub schema: Option<TableSchema>,
+}
+
+impl VariantSchema {
+    pub fn is_table(&self) -> bool {
+        self.schema.is_some()
+    }
+
+    pub fn is_tag(&self, raw: &TomlValue) -> bool {
+        raw.as_str() == Some(&self.value.tag)
+    }
+
+    pub fn flatten(&self, tagging: &Tagging) -> Vec<Section> {
+        let tag = &self.value.tag;
+        let mut sections = match self.schema.clone() {
+            Some(table) => table.flatten(),
+            None if tagging.has_tag_key() => {
+                let mut section = Section::default();
+                section.meta.defined_docs = self.docs.clone();
+                vec![section]
+            }
+            None => return Vec::new(),
+        };
+        for section in &mut sections {
+            section.assigned_to(tag);
+            section.variant_tag = Some(tag.clone());
+            section.meta.config.merge_parent(&self.config);
+            for block in &mut section.blocks {
+                block.meta.config.merge_parent(&self.config);
+            }
+        }
+        sections
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -47,18 +143,124 @@ pub struct PrimSchema {
 }
 
 impl PrimSchema {
-    pub fn flatten(self) -> Section {
+    pub fn flatten(self) -> Vec<Section> {
         let PrimSchema { meta, variants } = self;
         let array_index = if meta.is_array { Some(0) } else { None };
+        let map_depth = meta.map_depth;
+        let mut sections = Vec::new();
+        if !meta.is_array {
+            for variant in &variants {
+                sections.append(&mut variant.flatten(&meta.tagging));
+            }
+        }
         let block = Block {
             meta,
             variants,
             ..Default::default()
         };
-        Section {
+        let section = Section {
             array_index,
             blocks: vec![block],
             ..Default::default()
+        };
+        sections.insert(0, section);
+        for _ in 0..map_depth {
+            for section in &mut sections {
+                section.assigned_to(MAP_KEY);
+            }
+        }
+        sections
+    }
+
+    pub fn value_from(&self, raw: TomlValue) -> Result<Value, Error> {
+        if self.meta.is_array || self.variants.is_empty() {
+            return Ok(Value::new_prim(raw));
+        }
+        let found = match (&self.meta.tagging, &raw) {
+            (Tagging::External, TomlValue::Table(table)) if table.len() == 1 => table
+                .iter()
+                .find_map(|(tag, inner)| Some((self.variant_by_tag(tag)?, inner.clone()))),
+            (Tagging::Internal { tag }, TomlValue::Table(table)) => {
+                let mut inner = table.clone();
+                inner.remove(tag);
+                table
+                    .get(tag)
+                    .and_then(TomlValue::as_str)
+                    .and_then(|tag| self.variant_by_tag(tag))
+                    .map(|variant| (variant, TomlValue::Table(inner)))
+            }
+            (Tagging::Adjacent { tag, content }, TomlValue::Table(table)) => {
+                let inner = table
+                    .get(content)
+                    .cloned()
+                    .unwrap_or(TomlValue::Table(Map::new()));
+                table
+                    .get(tag)
+                    .and_then(TomlValue::as_str)
+                    .and_then(|tag| self.variant_by_tag(tag))
+                    .map(|variant| (variant, inner))
+            }
+            (Tagging::Untagged, TomlValue::Table(table)) => self
+                .variants
+                .iter()
+                .find(|variant| match &variant.schema {
+                    Some(schema) => table.keys().all(|key| schema.field(key).is_some()),
+                    None => false,
+                })
+                .map(|variant| (variant, raw.clone())),
+            _ => None,
+        };
+        let found = found.or_else(|| {
+            self.variants
+                .iter()
+                .find(|variant| variant.value.raw.as_ref() == Some(&raw) || variant.is_tag(&raw))
+                .map(|variant| (variant, raw.clone()))
+        });
+        let Some((variant, inner)) = found else {
+            return Err(Error::ValueTypeError(format!(
+                "{raw} is not a variant of {}",
+                self.meta.inner_type
+            )));
+        };
+        let prim = PrimValue {
+            tag: variant.value.tag.clone(),
+            raw: Some(raw),
+        };
+        match &variant.schema {
+            Some(schema) => {
+                let table = schema.table_value_from(inner)?;
+                Ok(Value::Variant(VariantValue { prim, table }))
+            }
+            None => Ok(Value::Prim(prim)),
+        }
+    }
+
+    fn variant_by_tag(&self, tag: &str) -> Option<&VariantSchema> {
+        self.variants
+            .iter()
+            .find(|variant| variant.value.tag == tag)
+    }
+
+    pub fn set_default(&mut self, value: Value) {
+        let prim = match value {
+            Value::Variant(VariantValue { prim, table }) => {
+                let variant = self
+                    .variants
+                    .iter_mut()
+                    .find(|variant| variant.value.tag == prim.tag);
+                if let Some(VariantSchema {
+                    schema: Some(schema),
+                    ..
+                }) = variant
+                {
+                    schema.set_default(table);
+                }
+                prim
+            }
+            value => value.into_prim(),
+        };
+        if prim.raw.is_some() {
+            self.meta.inner_default = prim;
         }
     }
 }
@@ -73,7 +275,14 @@ impl TableSchema {
     pub fn flatten(self) -> Vec<Section> {
         let TableSchema { meta, fields } = self;
         let mut sections = Vec::new();
-        for field in fields {
+        if fields.iter().all(|field| field.removed) {
+            sections.push(Section {
+                meta: meta.clone(),
+                array_index: if meta.is_array { Some(0) } else { None },
+                ..Default::default()
+            });
+        }
+        for field in fields.into_iter().filter(|field| !field.removed) {
             sections.append(&mut field.flatten());
         }
         Section::reduce(&mut sections);
@@ -82,9 +291,91 @@ impl TableSchema {
                 section.meta = meta.clone();
                 section.array_index = if meta.is_array { Some(0) } else { None };
             }
+            section.meta.config.merge_parent(&meta.config);
+            for block in &mut section.blocks {
+                block.meta.config.merge_parent(&meta.config);
+            }
+            for _ in 0..meta.map_depth {
+                section.assigned_to(MAP_KEY);
+            }
         }
         sections
     }
+
+    pub fn field(&self, ident: &str) -> Option<&FieldSchema> {
+        self.fields.iter().find(|field| field.is_named(ident))
+    }
+
+    pub fn value_from(&self, raw: TomlValue) -> Result<Value, Error> {
+        Ok(Value::Table(self.table_value_from(raw)?))
+    }
+
+    fn table_value_from(&self, raw: TomlValue) -> Result<TableValue, Error> {
+        let TomlValue::Table(mut table) = raw else {
+            return Err(Error::ValueTypeError(format!(
+                "{raw} is not a table of {}",
+                self.meta.inner_type
+            )));
+        };
+        let mut value = self.take_fields(&mut table)?;
+        // unknown keys stay in their table so they keep its array index
+        for (ident, raw) in table {
+            value.fields.push(FieldValue {
+                ident,
+                value: Value::new_prim(raw),
+                flat: false,
+            });
+        }
+        Ok(value)
+    }
+
+    // serde hands the keys left over by named fields to the flattened ones
+    fn take_fields(&self, table: &mut Map<String, TomlValue>) -> Result<TableValue, Error> {
+        let mut value = TableValue::default();
+        for field in self.fields.iter().filter(|field| !field.flat) {
+            let key = std::iter::once(&field.ident)
+                .chain(&field.aliases)
+                .find(|key| table.contains_key(*key));
+            let Some(raw) = key.and_then(|key| table.remove(key)) else {
+                continue;
+            };
+            value.fields.push(FieldValue {
+                ident: field.ident.clone(),
+                value: field.schema.value_from(raw)?,
+                flat: false,
+            });
+        }
+        for field in self.fields.iter().filter(|field| field.flat) {
+            let field_value = match &field.schema {
+                Schema::Table(schema) if !schema.meta.is_array && !schema.meta.is_map() => {
+                    Value::Table(schema.take_fields(table)?)
+                }
+                schema => {
+                    let raw = TomlValue::Table(std::mem::take(table));
+                    schema.value_from(raw)?
+                }
+            };
+            value.fields.push(FieldValue {
+                ident: field.ident.clone(),
+                value: field_value,
+                flat: true,
+            });
+        }
+        Ok(value)
+    }
+
+    pub fn set_default(&mut self, value: TableValue) {
+        self.meta.inner_default = value.clone().into_prim();
+        for field_value in value.fields {
+            let field = self
+                .fields
+                .iter_mut()
+                .find(|field| field.ident == field_value.ident);
+            if let Some(field) = field {
+                field.schema.set_default(field_value.value);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -94,6 +385,11 @@ pub struct FieldSchema {
     pub flat: bool,
     pub schema: Schema,
     pub config: TomlConfig,
+    pub renamed_from: Option<String>,
+    pub since: Option<String>,
+    pub removed: bool,
+    pub aliases: Vec<String>,
+    pub has_default: bool,
 }
 
 impl FieldSchema {
@@ -104,15 +400,32 @@ impl FieldSchema {
             flat,
             schema,
             config,
+            renamed_from,
+            since,
+            ..
         } = self;
         let mut sections = schema.flatten();
         if !flat {
             for section in &mut sections {
-                section.meta.valued_docs = docs.clone();
-                section.meta.config = config.clone();
+                // nested sections keep the docs and rename of their own field
+                let own = util::key_split(&section.key)
+                    .iter()
+                    .all(|ident| ident.is_empty() || *ident == MAP_KEY);
+                if section.variant_tag.is_none() {
+                    section.meta.valued_docs = docs.clone();
+                    if own {
+                        section.meta.renamed_from = renamed_from.clone();
+                        section.meta.since = since.clone();
+                    }
+                }
+                let mut section_config = config.clone();
+                section_config.merge_parent(&section.meta.config);
+                section.meta.config = section_config;
                 if section.is_value() {
                     for block in &mut section.blocks {
                         block.meta.valued_docs = docs.clone();
+                        block.meta.renamed_from = renamed_from.clone();
+                        block.meta.since = since.clone();
                         block.meta.config.merge_parent(&config);
                         block.key = ident.clone();
                         block.ident = ident.clone();
@@ -120,6 +433,7 @@ impl FieldSchema {
                 } else {
                     util::increase_key(&mut section.key, &ident);
                     for block in &mut section.blocks {
+                        block.meta.config.merge_parent(&config);
                         util::increase_key(&mut block.key, &ident);
                     }
                 }
@@ -128,6 +442,11 @@ impl FieldSchema {
         sections
     }
 
+    // serde reads a field under its name or any of its aliases
+    pub fn is_named(&self, ident: &str) -> bool {
+        self.ident == ident || self.aliases.iter().any(|alias| alias == ident)
+    }
+
     pub fn set_inner_default(&mut self, raw: TomlValue) {
         let meta = self.schema.meta_mut();
         meta.inner_default.raw = Some(raw);
@@ -149,7 +468,7 @@ impl Default for Schema {
 impl Schema {
     pub fn flatten(self) -> Vec<Section> {
         match self {
-            Schema::Prim(prim) => vec![prim.flatten()],
+            Schema::Prim(prim) => prim.flatten(),
             Schema::Table(table) => table.flatten(),
         }
     }
@@ -179,6 +498,63 @@ impl Schema {
         }
     }
 
+    pub fn set_default(&mut self, value: Value) {
+        match (self, value) {
+            (Schema::Prim(prim), value) => prim.set_default(value),
+            (Schema::Table(table), Value::Table(value))
+                if !table.meta.is_array && !table.meta.is_map() =>
+            {
+                table.set_default(value)
+            }
+            _ => {}
+        }
+    }
+
+    pub fn value_from(&self, raw: TomlValue) -> Result<Value, Error> {
+        let meta = self.meta();
+        if meta.is_map() {
+            let TomlValue::Table(table) = raw else {
+                return Err(Error::ValueTypeError(format!(
+                    "{raw} is not a map of {}",
+                    meta.inner_type
+                )));
+            };
+            let mut item = self.clone();
+            item.meta_mut().map_depth -= 1;
+            let mut value = TableValue::default();
+            for (ident, raw) in table {
+                let field = FieldValue {
+                    value: item.value_from(raw)?,
+                    ident,
+                    flat: false,
+                };
+                value.fields.push(field);
+            }
+            return Ok(Value::Table(value));
+        }
+        match self {
+            Schema::Prim(prim) => prim.value_from(raw),
+            Schema::Table(table) if table.meta.is_array => {
+                let TomlValue::Array(array) = raw else {
+                    return Err(Error::ValueTypeError(format!(
+                        "{raw} is not an array of {}",
+                        table.meta.inner_type
+                    )));
+                };
+                let mut values = Vec::new();
+                for raw in array {
+                    let item = table.table_value_from(raw)?;
+                    values.push(Value::Table(item));
+                }
+                Ok(Value::Array(ArrayValue { values }))
+            }
+            Schema::Table(table) => {
+                let item = table.table_value_from(raw)?;
+                Ok(Value::Table(item))
+            }
+        }
+    }
+
     pub fn set_wrap_type(&mut self, new: String) -> String {
         let meta = self.meta_mut();
         std::mem::replace(&mut meta.wrap_type, new)
@@ -1,12 +1,13 @@
 use std::collections::HashMap;
 
 use crate::{
-    BANG_COMMENT, ROOT_KEY,
+    BANG_COMMENT, MAP_KEY, ROOT_KEY,
     block::Block,
     comment::{Comment, CommentType},
     error::Error,
     schema::Meta,
     util,
+    yaml::Syntax,
 };
 
 #[derive(Debug, Clone)]
@@ -15,6 +16,7 @@ pub struct Section {
     pub meta: Meta,
     pub array_index: Option<usize>,
     pub blocks: Vec<Block>,
+    pub variant_tag: Option<String>,
 }
 
 impl Default for Section {
@@ -24,6 +26,7 @@ impl Default for Section {
             meta: Meta::default(),
             array_index: None,
             blocks: Vec::new(),
+            variant_tag: None,
         }
     }
 }
@@ -36,8 +39,18 @@ impl Section {
         self.key == ROOT_KEY && self.blocks.len() == 1 && self.blocks[0].is_value()
     }
 
+    pub fn is_template(&self) -> bool {
+        util::is_template_key(&self.key)
+    }
+
     pub fn is_commented(&self) -> bool {
-        let mut commented = self.meta.config.commented;
+        if self.is_template() {
+            return true;
+        }
+        if self.blocks.is_empty() {
+            return self.meta.config.is_commented() && self.meta.is_option_type();
+        }
+        let mut commented = self.meta.config.is_commented();
         for block in &self.blocks {
             commented = commented && block.is_comented();
         }
@@ -54,9 +67,10 @@ impl Section {
 
     pub fn assigned_to(&mut self, ident: impl AsRef<str>) {
         if self.is_value() {
+            let ident = util::key_quote(ident.as_ref());
             for block in &mut self.blocks {
-                block.key = ident.as_ref().to_string();
-                block.ident = ident.as_ref().to_string();
+                block.key = ident.clone();
+                block.ident = ident.clone();
             }
         } else {
             util::increase_key(&mut self.key, &ident);
@@ -66,6 +80,16 @@ impl Section {
         }
     }
 
+    pub fn instantiate(&mut self, key: &str) {
+        self.key = util::key_fill(&self.key, key);
+        for block in &mut self.blocks {
+            block.key = util::key_fill(&block.key, &self.key);
+            if block.ident == MAP_KEY {
+                block.ident = util::key_ident(&block.key);
+            }
+        }
+    }
+
     pub fn reduce(sections: &mut Vec<Section>) {
         let mut map: HashMap<String, &mut Section> = HashMap::new();
         let mut dup = Vec::new();
@@ -83,6 +107,10 @@ impl Section {
     }
 
     pub fn render(&self) -> Result<String, Error> {
+        self.render_marked(BANG_COMMENT)
+    }
+
+    pub(crate) fn render_marked(&self, bang: &str) -> Result<String, Error> {
         if self.is_none_skipped() {
             return Ok(String::new());
         }
@@ -99,28 +127,90 @@ impl Section {
         } else {
             ("[".to_string(), "]".to_string())
         };
-        let bang = if self.is_commented() && (self.key != ROOT_KEY) {
-            BANG_COMMENT
+        let marker = if self.is_commented() && (self.key != ROOT_KEY) {
+            bang
         } else {
             ""
         };
-        lines.push(format!("{bang}{}{}{}", left, self.key, right));
+        lines.push(format!("{marker}{}{}{}", left, self.key, right));
         for block in &self.blocks {
-            let line = block.render()?;
+            let line = block.render_as(Syntax::Toml, bang)?;
             if !line.is_empty() {
                 lines.push(line);
             }
         }
+        Ok(lines.join("\n").trim_end().to_string())
+    }
+
+    pub fn alternative(&self) -> Section {
+        let mut section = self.clone();
+        section.meta.config.commented = Some(true);
+        for block in &mut section.blocks {
+            block.value = None;
+            block.meta.config.commented = Some(true);
+        }
+        section
+    }
+
+    pub fn render_variant(&self, block: &Block, selected: bool) -> Result<String, Error> {
+        self.render_variant_marked(block, selected, BANG_COMMENT)
+    }
+
+    pub(crate) fn render_variant_marked(
+        &self,
+        block: &Block,
+        selected: bool,
+        bang: &str,
+    ) -> Result<String, Error> {
+        let mut section = if selected {
+            self.clone()
+        } else {
+            self.alternative()
+        };
+        let tag = self.variant_tag.clone().unwrap_or_default();
+        let variant_key = block.variant_section_key(&tag).unwrap_or_default();
+        let rest = self.key.strip_prefix(&variant_key).unwrap_or_default();
+        section.key = block.variant_content_key(&tag) + rest;
+        if !rest.is_empty() || !block.meta.tagging.has_tag_key() {
+            return section.render_marked(bang);
+        }
+        let commented = !selected || block.is_comented();
+        let marker = if commented { bang } else { "" };
+        let mut lines = Vec::new();
+        if !section.meta.config.is_comment_hidden() {
+            lines.push(section.comment().render()?);
+        }
+        if !block.key.is_empty() {
+            lines.push(format!("{marker}[{}]", block.key));
+        }
+        let syntax = Syntax::Toml;
+        lines.append(&mut block.render_variant_tag_as(syntax, bang, &tag, commented, selected));
+        if section.key != block.key && !section.blocks.is_empty() {
+            lines.push(format!("{marker}[{}]", section.key));
+        }
+        for block in &section.blocks {
+            let line = block.render_as(syntax, bang)?;
+            if !line.is_empty() {
+                lines.push(line);
+            }
+        }
+        lines.retain(|line| !line.is_empty());
         Ok(lines.join("\n"))
     }
 
     pub fn comment(&self) -> Comment {
         let mut comment = self.meta.comment();
+        comment.valued_docs = self.meta.valued_docs_at(&self.key);
         comment.comment_type = if self.is_root() {
             CommentType::Root
         } else {
             CommentType::Section
         };
+        // the enum's block already carries the type of a variant section
+        if self.variant_tag.is_some() {
+            comment.inner_type.clear();
+            comment.type_name = None;
+        }
         comment
     }
 }
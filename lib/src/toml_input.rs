@@ -1,10 +1,15 @@
-use std::path::PathBuf;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
 
 use crate::{
     Schema, TomlContent, TomlValue, Value,
+    config::RenderOptions,
     error::Error,
+    migrate::MigrationReport,
     schema::{Meta, PrimSchema},
-    value::{ArrayValue, PrimValue},
+    value::{ArrayValue, FieldValue, PrimValue, TableValue},
 };
 use serde::Serialize;
 
@@ -12,27 +17,49 @@ pub trait TomlInput: Serialize + Sized {
     fn schema() -> Result<Schema, Error>;
     fn into_value(self) -> Result<Value, Error>;
     fn schema_to_string() -> Result<String, Error> {
+        Self::schema_to_string_with(&RenderOptions::default())
+    }
+    fn schema_to_string_with(options: &RenderOptions) -> Result<String, Error> {
         let schema = Self::schema()?;
         let sections = schema.flatten();
-        let mut content = TomlContent { sections };
-        content.config_commented(false);
-        content.render()
+        let mut content = TomlContent::new(sections);
+        content.fill_commented(false);
+        content.render_with(options)
     }
     fn into_string(self) -> Result<String, Error> {
+        self.into_string_with(&RenderOptions::default())
+    }
+    fn into_string_with(self, options: &RenderOptions) -> Result<String, Error> {
+        let content = self.into_content()?;
+        content.render_with(options)
+    }
+    fn migrate_str(existing: &str) -> Result<MigrationReport, Error> {
+        Self::schema()?.migrate(existing)
+    }
+    // keys the schema does not know come back commented out under `# Unrecognized
+    // keys`, like `#!prot = 3`, so those settings no longer take effect
+    fn upgrade_str(existing: &str) -> Result<String, Error> {
         let schema = Self::schema()?;
-        let sections = schema.flatten();
-        let mut content = TomlContent { sections };
-        let value = self.into_value()?;
-        content.merge_value(value);
+        let migrated = schema.migrate(existing)?;
+        let value = TomlContent::parse(schema.clone(), &migrated.text)?;
+        let mut content = TomlContent::new(schema.flatten());
+        content.merge_value_trailing(value);
+        content.fill_commented_optional();
         content.render()
     }
+    // drops the warnings of `into_content_with_warnings`
     fn into_content(self) -> Result<TomlContent, Error> {
+        let (content, _) = self.into_content_with_warnings()?;
+        Ok(content)
+    }
+    // the warnings `TomlContent::merge_value` gives for values without a block
+    fn into_content_with_warnings(self) -> Result<(TomlContent, Vec<String>), Error> {
         let schema = Self::schema()?;
         let sections = schema.flatten();
-        let mut content = TomlContent { sections };
+        let mut content = TomlContent::new(sections);
         let value = self.into_value()?;
-        content.merge_value(value);
-        Ok(content)
+        let warnings = content.merge_value(value);
+        Ok((content, warnings))
     }
 }
 
@@ -75,6 +102,44 @@ impl_type_info_primary!(f32, "f32");
 impl_type_info_primary!(f64, "f64");
 impl_type_info_primary!(PathBuf, "path");
 
+impl TomlInput for TomlValue {
+    fn schema() -> Result<Schema, Error> {
+        let meta = Meta {
+            inner_type: "value".to_string(),
+            ..Default::default()
+        };
+        let data = PrimSchema {
+            meta,
+            ..Default::default()
+        };
+        Ok(Schema::Prim(data))
+    }
+    fn into_value(self) -> Result<Value, Error> {
+        Ok(Value::new_prim(self))
+    }
+}
+
+impl TomlInput for toml::Table {
+    fn schema() -> Result<Schema, Error> {
+        let mut schema = TomlValue::schema()?;
+        schema.set_wrap_type("Table".to_string());
+        schema.meta_mut().map_depth += 1;
+        Ok(schema)
+    }
+    fn into_value(self) -> Result<Value, Error> {
+        let mut table = TableValue::default();
+        for (key, raw) in self {
+            let field = FieldValue {
+                ident: key,
+                value: Value::new_prim(raw),
+                flat: false,
+            };
+            table.fields.push(field);
+        }
+        Ok(Value::Table(table))
+    }
+}
+
 impl<T: TomlInput> TomlInput for Option<T> {
     fn schema() -> Result<Schema, Error> {
         let mut schema = T::schema()?;
@@ -119,3 +184,37 @@ impl<T: TomlInput> TomlInput for Vec<T> {
         }
     }
 }
+
+impl<T: TomlInput> TomlInput for BTreeMap<String, T> {
+    fn schema() -> Result<Schema, Error> {
+        let mut schema = T::schema()?;
+        schema.set_wrap_type("BTreeMap".to_string());
+        schema.meta_mut().map_depth += 1;
+        Ok(schema)
+    }
+    fn into_value(self) -> Result<Value, Error> {
+        let mut table = TableValue::default();
+        for (key, item) in self {
+            let field = FieldValue {
+                ident: key,
+                value: item.into_value()?,
+                flat: false,
+            };
+            table.fields.push(field);
+        }
+        Ok(Value::Table(table))
+    }
+}
+
+impl<T: TomlInput> TomlInput for HashMap<String, T> {
+    fn schema() -> Result<Schema, Error> {
+        let mut schema = T::schema()?;
+        schema.set_wrap_type("HashMap".to_string());
+        schema.meta_mut().map_depth += 1;
+        Ok(schema)
+    }
+    fn into_value(self) -> Result<Value, Error> {
+        let map: BTreeMap<String, T> = self.into_iter().collect();
+        map.into_value()
+    }
+}
@@ -1,6 +1,6 @@
 use serde::Serialize;
 
-use crate::{COMMENT, TAG};
+use crate::{COMMENT, MAP_KEY, TAG, TomlValue};
 
 pub fn value_to_string<T: Serialize>(value: &T) -> Result<String, toml::ser::Error> {
     let mut ser_value = String::new();
@@ -33,15 +33,105 @@ pub fn remove_prefix_tag(key: &str) -> String {
 }
 
 pub fn increase_key(key: &mut String, ident: impl AsRef<str>) {
+    let ident = key_quote(ident.as_ref());
     if key.is_empty() {
-        *key = ident.as_ref().to_string();
+        *key = ident;
     } else {
-        *key = format!("{}{}{}", ident.as_ref(), TAG, key);
+        *key = format!("{ident}{TAG}{key}");
     }
 }
 
+pub fn key_child(key: &str, ident: &str) -> String {
+    key_join(key, &key_quote(ident))
+}
+
+// joins key paths, use `key_child` for a single ident
+pub fn key_join(key: &str, path: &str) -> String {
+    if key.is_empty() {
+        path.to_string()
+    } else {
+        format!("{key}{TAG}{path}")
+    }
+}
+
+// map keys come from user data, anything but a bare key is quoted so it stays
+// one ident of the dotted key
+pub fn key_quote(ident: &str) -> String {
+    let bare = !ident.is_empty()
+        && ident
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare || ident == MAP_KEY {
+        ident.to_string()
+    } else {
+        TomlValue::String(ident.to_string()).to_string()
+    }
+}
+
+pub fn key_unquote(ident: &str) -> String {
+    match ident.parse::<toml_edit::Key>() {
+        Ok(key) => key.get().to_string(),
+        Err(_) => ident.to_string(),
+    }
+}
+
+// splits on the tags outside of quoted idents
+pub fn key_split(key: &str) -> Vec<&str> {
+    let mut idents = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in key.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if key[i..].starts_with(TAG) => {
+                idents.push(&key[start..i]);
+                start = i + TAG.len();
+            }
+            None => {}
+        }
+    }
+    idents.push(&key[start..]);
+    idents
+}
+
 pub fn key_parent(key: &str) -> String {
-    let mut idents: Vec<_> = key.split(TAG).collect();
+    let mut idents = key_split(key);
     idents.pop();
     idents.join(TAG)
 }
+
+pub fn is_template_key(key: &str) -> bool {
+    key_split(key).into_iter().any(|ident| ident == MAP_KEY)
+}
+
+pub fn key_match(template: &str, key: &str) -> bool {
+    let templates = key_split(template);
+    let idents = key_split(key);
+    templates.len() == idents.len()
+        && templates
+            .iter()
+            .zip(&idents)
+            .all(|(template, ident)| template == ident || *template == MAP_KEY)
+}
+
+pub fn key_fill(template: &str, key: &str) -> String {
+    let idents = key_split(key);
+    let filled: Vec<_> = key_split(template)
+        .into_iter()
+        .enumerate()
+        .map(|(i, template)| match idents.get(i) {
+            Some(ident) if template == MAP_KEY => *ident,
+            _ => template,
+        })
+        .collect();
+    filled.join(TAG)
+}
+
+pub fn key_ident(key: &str) -> String {
+    key_split(key).pop().unwrap_or(key).to_string()
+}
@@ -0,0 +1,508 @@
+use std::fmt;
+
+use serde::Deserialize;
+use toml_edit::{ImDocument, Item, Table, TableLike, Value as EditValue};
+
+use crate::{
+    Schema, TomlValue,
+    schema::{PrimSchema, TableSchema, Tagging, VariantSchema},
+    util,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    Syntax,
+    UnknownKey,
+    TypeMismatch,
+    InvalidVariant,
+    WrongShape,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub key: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    pub expected: String,
+    pub docs: String,
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)?;
+        if !self.expected.is_empty() {
+            write!(f, "; expected {}", self.expected)?;
+        }
+        if !self.docs.is_empty() {
+            write!(f, " ({})", self.docs)?;
+        }
+        Ok(())
+    }
+}
+
+impl Schema {
+    pub fn validate(&self, text: &str) -> Vec<Diagnostic> {
+        let mut validator = Validator {
+            text,
+            diagnostics: Vec::new(),
+        };
+        match ImDocument::parse(text) {
+            Ok(document) => {
+                let node = Node::Item(document.as_item());
+                validator.schema(self, node, "", 0, "");
+            }
+            Err(err) => {
+                let offset = err.span().map_or(0, |span| span.start);
+                validator.push(DiagnosticKind::Syntax, "", offset, err.message(), "", "");
+            }
+        }
+        validator
+            .diagnostics
+            .sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+        validator.diagnostics
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    Item(&'a Item),
+    Value(&'a EditValue),
+    Table(&'a Table),
+}
+
+impl<'a> Node<'a> {
+    fn span_start(&self) -> Option<usize> {
+        let span = match self {
+            Node::Item(item) => item.span(),
+            Node::Value(value) => value.span(),
+            Node::Table(table) => table.span(),
+        };
+        span.map(|span| span.start)
+    }
+
+    fn value(&self) -> Option<&'a EditValue> {
+        match self {
+            Node::Item(item) => item.as_value(),
+            Node::Value(value) => Some(value),
+            Node::Table(_) => None,
+        }
+    }
+
+    fn table(&self) -> Option<&'a dyn TableLike> {
+        match self {
+            Node::Item(item) => item.as_table_like(),
+            Node::Value(value) => value.as_inline_table().map(|table| table as &dyn TableLike),
+            Node::Table(table) => Some(*table as &dyn TableLike),
+        }
+    }
+
+    fn array(&self) -> Option<Vec<Node<'a>>> {
+        if let Node::Item(Item::ArrayOfTables(array)) = self {
+            return Some(array.iter().map(Node::Table).collect());
+        }
+        let array = self.value()?.as_array()?;
+        Some(array.iter().map(Node::Value).collect())
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Node::Item(item) => item.type_name(),
+            Node::Value(value) => value.type_name(),
+            Node::Table(_) => "table",
+        }
+    }
+
+    fn raw(&self) -> Option<TomlValue> {
+        let text = self.value()?.to_string();
+        TomlValue::deserialize(toml::de::ValueDeserializer::new(text.trim())).ok()
+    }
+}
+
+struct Validator<'t> {
+    text: &'t str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator<'_> {
+    fn push(
+        &mut self,
+        kind: DiagnosticKind,
+        key: &str,
+        offset: usize,
+        message: impl Into<String>,
+        expected: impl Into<String>,
+        docs: &str,
+    ) {
+        let before = &self.text[..offset.min(self.text.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        let column = before[line_start..].chars().count() + 1;
+        self.diagnostics.push(Diagnostic {
+            kind,
+            key: key.to_string(),
+            line,
+            column,
+            message: message.into(),
+            expected: expected.into(),
+            docs: docs.trim().to_string(),
+            suggestion: None,
+        });
+    }
+
+    fn suggest(&mut self, suggestion: Option<String>) {
+        let Some(diagnostic) = self.diagnostics.last_mut() else {
+            return;
+        };
+        if let Some(suggestion) = &suggestion {
+            diagnostic.message += &format!(", did you mean `{suggestion}`?");
+        }
+        diagnostic.suggestion = suggestion;
+    }
+
+    fn wrong_shape(&mut self, key: &str, node: Node, at: usize, expected: String, docs: &str) {
+        let message = format!("`{key}` has the wrong shape: found {}", node.type_name());
+        self.push(DiagnosticKind::WrongShape, key, at, message, expected, docs);
+    }
+
+    fn schema(&mut self, schema: &Schema, node: Node, key: &str, at: usize, docs: &str) {
+        let at = node.span_start().unwrap_or(at);
+        let meta = schema.meta();
+        let docs = if docs.trim().is_empty() {
+            &meta.defined_docs
+        } else {
+            docs
+        };
+        if meta.is_map() {
+            let Some(table) = node.table() else {
+                let expected = format!("a table of {}", meta.inner_type);
+                return self.wrong_shape(key, node, at, expected, docs);
+            };
+            let mut item = schema.clone();
+            item.meta_mut().map_depth -= 1;
+            for (ident, value) in table.iter() {
+                let item_key = util::key_child(key, ident);
+                let item_at = key_start(table, ident).unwrap_or(at);
+                self.schema(&item, Node::Item(value), &item_key, item_at, docs);
+            }
+            return;
+        }
+        match schema {
+            Schema::Prim(prim) if prim.meta.is_array => {
+                let Some(items) = node.array() else {
+                    let expected = format!("an array of {}", prim.meta.inner_type);
+                    return self.wrong_shape(key, node, at, expected, docs);
+                };
+                let mut item = prim.clone();
+                item.meta.is_array = false;
+                for node in items {
+                    self.prim(&item, node, key, at, docs);
+                }
+            }
+            Schema::Prim(prim) => self.prim(prim, node, key, at, docs),
+            Schema::Table(table) if table.meta.is_array => {
+                let Some(items) = node.array() else {
+                    let expected = format!("an array of tables of {}", table.meta.inner_type);
+                    return self.wrong_shape(key, node, at, expected, docs);
+                };
+                for node in items {
+                    self.table(table, node, key, at, docs, None);
+                }
+            }
+            Schema::Table(table) => self.table(table, node, key, at, docs, None),
+        }
+    }
+
+    fn table(
+        &mut self,
+        schema: &TableSchema,
+        node: Node,
+        key: &str,
+        at: usize,
+        docs: &str,
+        skip: Option<&str>,
+    ) {
+        let at = node.span_start().unwrap_or(at);
+        let Some(table) = node.table() else {
+            let expected = format!("a table of {}", schema.meta.inner_type);
+            return self.wrong_shape(key, node, at, expected, docs);
+        };
+        let mut remaining: Vec<&str> = table
+            .iter()
+            .map(|(ident, _)| ident)
+            .filter(|ident| Some(*ident) != skip)
+            .collect();
+        self.fields(schema, table, key, at, &mut remaining);
+        for ident in remaining {
+            let field_key = util::key_child(key, ident);
+            let field_at = key_start(table, ident).unwrap_or(at);
+            let message = format!("unknown key `{field_key}`");
+            let known = known_keys(schema);
+            let expected: Vec<_> = known.iter().map(|ident| format!("`{ident}`")).collect();
+            self.push(
+                DiagnosticKind::UnknownKey,
+                &field_key,
+                field_at,
+                message,
+                expected.join(", "),
+                docs,
+            );
+            self.suggest(closest(ident, &known));
+        }
+    }
+
+    // mirrors `TableSchema::take_fields`, flattened fields see the leftover keys
+    fn fields(
+        &mut self,
+        schema: &TableSchema,
+        table: &dyn TableLike,
+        key: &str,
+        at: usize,
+        remaining: &mut Vec<&str>,
+    ) {
+        for field in schema
+            .fields
+            .iter()
+            .filter(|field| !field.flat && !field.removed)
+        {
+            let found = std::iter::once(&field.ident)
+                .chain(&field.aliases)
+                .find_map(|ident| table.get_key_value(ident));
+            let Some((ident, item)) = found else {
+                continue;
+            };
+            let ident = ident.get();
+            remaining.retain(|remaining| *remaining != ident);
+            let field_key = util::key_child(key, ident);
+            let field_at = key_start(table, ident).unwrap_or(at);
+            self.schema(
+                &field.schema,
+                Node::Item(item),
+                &field_key,
+                field_at,
+                &field.docs,
+            );
+        }
+        for field in schema.fields.iter().filter(|field| field.flat) {
+            match &field.schema {
+                Schema::Table(inner) if !inner.meta.is_array && !inner.meta.is_map() => {
+                    self.fields(inner, table, key, at, remaining);
+                }
+                Schema::Table(inner) if inner.meta.is_map() => {
+                    let mut item = field.schema.clone();
+                    item.meta_mut().map_depth -= 1;
+                    for ident in std::mem::take(remaining) {
+                        let Some((_, value)) = table.get_key_value(ident) else {
+                            continue;
+                        };
+                        let item_key = util::key_child(key, ident);
+                        let item_at = key_start(table, ident).unwrap_or(at);
+                        self.schema(&item, Node::Item(value), &item_key, item_at, &field.docs);
+                    }
+                }
+                _ => remaining.clear(),
+            }
+        }
+    }
+
+    fn prim(&mut self, schema: &PrimSchema, node: Node, key: &str, at: usize, docs: &str) {
+        let at = node.span_start().unwrap_or(at);
+        if !schema.variants.is_empty() {
+            return self.variant(schema, node, key, at, docs);
+        }
+        let inner_type = &schema.meta.inner_type;
+        let Some(value) = node.value() else {
+            return self.wrong_shape(key, node, at, inner_type.clone(), docs);
+        };
+        if prim_matches(inner_type, value) == Some(false) {
+            let message = format!("`{key}` has type {}", node.type_name());
+            self.push(
+                DiagnosticKind::TypeMismatch,
+                key,
+                at,
+                message,
+                inner_type.clone(),
+                docs,
+            );
+        }
+    }
+
+    fn variant(&mut self, schema: &PrimSchema, node: Node, key: &str, at: usize, docs: &str) {
+        let by_tag = |tag: &str| {
+            schema
+                .variants
+                .iter()
+                .find(|variant| variant.value.tag == tag)
+        };
+        let table = node.table();
+        let found = match (&schema.meta.tagging, table) {
+            (Tagging::External, Some(table)) if table.len() == 1 => {
+                table.iter().find_map(|(tag, item)| {
+                    let inner_key = util::key_child(key, &by_tag(tag)?.value.tag);
+                    Some((by_tag(tag)?, Node::Item(item), inner_key, None))
+                })
+            }
+            (Tagging::Internal { tag }, Some(table)) => table
+                .get(tag)
+                .and_then(Item::as_str)
+                .and_then(by_tag)
+                .map(|variant| (variant, node, key.to_string(), Some(tag.as_str()))),
+            (Tagging::Adjacent { tag, content }, Some(table)) => {
+                let variant = table.get(tag).and_then(Item::as_str).and_then(by_tag);
+                match (variant, table.get(content)) {
+                    (Some(variant), Some(inner)) => {
+                        let inner_key = util::key_child(key, content);
+                        Some((variant, Node::Item(inner), inner_key, None))
+                    }
+                    (Some(variant), None) => {
+                        return self.variant_fields(variant, None, key, at, docs);
+                    }
+                    _ => None,
+                }
+            }
+            (Tagging::Untagged, Some(table)) => schema
+                .variants
+                .iter()
+                .find(|variant| match &variant.schema {
+                    Some(schema) => table.iter().all(|(ident, _)| schema.field(ident).is_some()),
+                    None => false,
+                })
+                .map(|variant| (variant, node, key.to_string(), None)),
+            _ => None,
+        };
+        if let Some((variant, inner, inner_key, skip)) = found {
+            let inner = Some((inner, skip));
+            return self.variant_fields(variant, inner, &inner_key, at, docs);
+        }
+        let raw = node.raw();
+        let found = raw.as_ref().and_then(|raw| {
+            schema
+                .variants
+                .iter()
+                .find(|variant| variant.value.raw.as_ref() == Some(raw) || variant.is_tag(raw))
+        });
+        if found.is_some() {
+            return;
+        }
+        let found = match &raw {
+            Some(raw) => raw.to_string(),
+            None => node.type_name().to_string(),
+        };
+        let message = format!(
+            "`{key}` = {found} is not a variant of {}",
+            schema.meta.inner_type
+        );
+        let expected = variant_names(&schema.variants);
+        self.push(
+            DiagnosticKind::InvalidVariant,
+            key,
+            at,
+            message,
+            expected,
+            docs,
+        );
+        if let Some(text) = raw.as_ref().and_then(TomlValue::as_str) {
+            let names: Vec<_> = schema
+                .variants
+                .iter()
+                .map(
+                    |variant| match variant.value.raw.as_ref().and_then(TomlValue::as_str) {
+                        Some(name) => name.to_string(),
+                        None => variant.value.tag.clone(),
+                    },
+                )
+                .collect();
+            self.suggest(closest(text, &names));
+        }
+    }
+
+    fn variant_fields(
+        &mut self,
+        variant: &VariantSchema,
+        inner: Option<(Node, Option<&str>)>,
+        key: &str,
+        at: usize,
+        docs: &str,
+    ) {
+        let docs = if variant.docs.trim().is_empty() {
+            docs
+        } else {
+            &variant.docs
+        };
+        match (&variant.schema, inner) {
+            (Some(schema), Some((node, skip))) => self.table(schema, node, key, at, docs, skip),
+            (None, Some((node, None))) if node.table().is_some_and(|table| !table.is_empty()) => {
+                let expected = format!("unit variant {}", variant.value.tag);
+                self.wrong_shape(key, node, at, expected, docs);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn key_start(table: &dyn TableLike, ident: &str) -> Option<usize> {
+    table.key(ident)?.span().map(|span| span.start)
+}
+
+fn known_keys(schema: &TableSchema) -> Vec<String> {
+    let mut keys = Vec::new();
+    for field in &schema.fields {
+        match &field.schema {
+            Schema::Table(inner) if field.flat && !inner.meta.is_array && !inner.meta.is_map() => {
+                keys.append(&mut known_keys(inner));
+            }
+            _ if field.flat || field.removed => {}
+            _ => {
+                keys.push(field.ident.clone());
+                keys.extend(field.aliases.iter().cloned());
+            }
+        }
+    }
+    keys
+}
+
+// same cutoff as cargo's "did you mean", a third of the typed length
+fn closest(text: &str, candidates: &[String]) -> Option<String> {
+    let len = text.chars().count();
+    let limit = (len / 3).max(1);
+    candidates
+        .iter()
+        .map(|candidate| (strsim::damerau_levenshtein(text, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit && *distance < len)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.clone())
+}
+
+fn variant_names(variants: &[VariantSchema]) -> String {
+    let names: Vec<_> = variants
+        .iter()
+        .map(|variant| match &variant.value.raw {
+            Some(raw) => raw.to_string(),
+            None => format!("\"{}\"", variant.value.tag),
+        })
+        .collect();
+    names.join(", ")
+}
+
+fn prim_matches(inner_type: &str, value: &EditValue) -> Option<bool> {
+    let integer = value.as_integer();
+    let fits = match inner_type {
+        "bool" => value.is_bool(),
+        "string" | "path" => value.is_str(),
+        "f32" | "f64" => value.is_float() || value.is_integer(),
+        "i8" => integer.is_some_and(|n| i8::try_from(n).is_ok()),
+        "i16" => integer.is_some_and(|n| i16::try_from(n).is_ok()),
+        "i32" => integer.is_some_and(|n| i32::try_from(n).is_ok()),
+        "i64" => integer.is_some(),
+        "isize" => integer.is_some_and(|n| isize::try_from(n).is_ok()),
+        "u8" => integer.is_some_and(|n| u8::try_from(n).is_ok()),
+        "u16" => integer.is_some_and(|n| u16::try_from(n).is_ok()),
+        "u32" => integer.is_some_and(|n| u32::try_from(n).is_ok()),
+        "u64" => integer.is_some_and(|n| u64::try_from(n).is_ok()),
+        "usize" => integer.is_some_and(|n| usize::try_from(n).is_ok()),
+        _ => return None,
+    };
+    Some(fits)
+}
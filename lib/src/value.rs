@@ -95,11 +95,31 @@ impl TableValue {
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct VariantValue {
+    pub prim: PrimValue,
+    pub table: TableValue,
+}
+
+impl VariantValue {
+    pub fn flatten(self) -> Vec<BlockValue> {
+        let VariantValue { prim, table } = self;
+        let tag = prim.tag.clone();
+        let mut blocks = vec![prim.flatten()];
+        for mut block in table.flatten() {
+            util::increase_key(&mut block.key, &tag);
+            blocks.push(block);
+        }
+        blocks
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Value {
     Prim(PrimValue),
     Array(ArrayValue),
     Table(TableValue),
+    Variant(VariantValue),
 }
 
 impl Default for Value {
@@ -119,6 +139,7 @@ impl Value {
             Value::Prim(prim) => prim,
             Value::Array(array) => array.into_prim(),
             Value::Table(table) => table.into_prim(),
+            Value::Variant(variant) => variant.prim,
         }
     }
 
@@ -134,11 +155,16 @@ impl Value {
         matches!(self, Value::Table(_))
     }
 
+    pub fn is_variant(&self) -> bool {
+        matches!(self, Value::Variant(_))
+    }
+
     pub fn flatten(self) -> Vec<BlockValue> {
         match self {
             Value::Prim(prim) => vec![prim.flatten()],
             Value::Array(array) => array.flatten(),
             Value::Table(table) => table.flatten(),
+            Value::Variant(variant) => variant.flatten(),
         }
     }
 }
@@ -155,10 +181,8 @@ impl FieldValue {
         let FieldValue { ident, value, flat } = self;
         let PrimValue { raw, .. } = value.into_prim();
         let mut map = Map::new();
-        if flat {
-            if let Some(TomlValue::Table(map)) = raw {
-                return map;
-            }
+        if flat && let Some(TomlValue::Table(map)) = raw {
+            return map;
         }
         if let Some(raw) = raw {
             map.insert(ident, raw);
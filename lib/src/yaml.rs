@@ -0,0 +1,452 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    BANG_COMMENT, COMMENT, Error, MAP_KEY, ROOT_KEY, TomlContent, TomlValue, UNKNOWN_KEYS,
+    block::Block, schema::Tagging, section::Section, util,
+};
+
+const INDENT: &str = "  ";
+const ITEM: &str = "- ";
+
+// blocks only differ in how a key and its value are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Syntax {
+    Toml,
+    Yaml,
+}
+
+impl Syntax {
+    pub(crate) fn assign(self, key: &str, value: &TomlValue) -> String {
+        self.assign_text(key, &self.value(value))
+    }
+
+    pub(crate) fn assign_text(self, key: &str, value: &str) -> String {
+        match self {
+            Syntax::Toml => format!("{key} = {value}"),
+            Syntax::Yaml => format!("{}: {value}", yaml_key(&util::key_unquote(key))),
+        }
+    }
+
+    pub(crate) fn value(self, value: &TomlValue) -> String {
+        match self {
+            Syntax::Toml => value.to_string(),
+            Syntax::Yaml => yaml_value(value),
+        }
+    }
+}
+
+impl TomlContent {
+    pub fn render_yaml(&self) -> Result<String, Error> {
+        let mut writer = Writer::default();
+        for section in nested(&self.sections) {
+            writer.separate();
+            match self.variant_block(section) {
+                Some(block) => {
+                    let selected = section.variant_tag.as_deref() == Some(block.selected_tag());
+                    if !selected && !block.enum_is_expand() {
+                        continue;
+                    }
+                    render_variant(&mut writer, section, block, selected)?;
+                }
+                None => render_section(&mut writer, section, None)?,
+            }
+        }
+        if !self.unknown.is_empty() {
+            writer.separate();
+            writer.push(format!("{COMMENT} {UNKNOWN_KEYS}"));
+            for (key, value) in &self.unknown {
+                let line = Syntax::Yaml.assign(key, value);
+                writer.push(format!("{BANG_COMMENT}{line}"));
+            }
+        }
+        Ok(writer.finish())
+    }
+}
+
+// array items added by `merge_value` come after the other sections, which TOML
+// allows but a YAML mapping can't reopen a key, so each subtree is grouped where
+// it first appears
+fn nested(sections: &[Section]) -> Vec<&Section> {
+    let arrays: HashSet<_> = sections
+        .iter()
+        .filter(|section| section.meta.is_array)
+        .map(|section| section.key.as_str())
+        .collect();
+    let mut ranks = HashMap::new();
+    let mut keyed = Vec::new();
+    for section in sections {
+        let mut path = Vec::new();
+        let mut key = String::new();
+        if section.key != ROOT_KEY {
+            for ident in util::key_split(&section.key) {
+                key = util::key_join(&key, ident);
+                let next = ranks.len();
+                path.push(*ranks.entry((key.clone(), None)).or_insert(next));
+                if arrays.contains(key.as_str()) && section.array_index.is_some() {
+                    let next = ranks.len();
+                    let index = section.array_index;
+                    path.push(*ranks.entry((key.clone(), index)).or_insert(next));
+                }
+            }
+        }
+        keyed.push((path, section));
+    }
+    keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+    keyed.into_iter().map(|(_, section)| section).collect()
+}
+
+fn render_section(
+    writer: &mut Writer,
+    section: &Section,
+    tag: Option<(usize, &str)>,
+) -> Result<(), Error> {
+    if section.is_none_skipped() {
+        return Ok(());
+    }
+    let mut part = Part {
+        key: &section.key,
+        tag,
+        map_depth: section.meta.map_depth,
+        empty: empty_collection(section),
+        is_array: section.meta.is_array,
+        array_index: section.array_index,
+        commented: section.is_commented() && section.key != ROOT_KEY,
+        comment: String::new(),
+        lines: Vec::new(),
+    };
+    if !section.meta.config.is_comment_hidden() {
+        part.comment = section.comment().render()?;
+    }
+    for block in &section.blocks {
+        part.lines
+            .push(block.render_as(Syntax::Yaml, BANG_COMMENT)?);
+    }
+    writer.section(part);
+    Ok(())
+}
+
+// mirrors `Section::render_variant`, the tag lines open the block's own mapping
+fn render_variant(
+    writer: &mut Writer,
+    variant: &Section,
+    block: &Block,
+    selected: bool,
+) -> Result<(), Error> {
+    let mut section = if selected {
+        variant.clone()
+    } else {
+        variant.alternative()
+    };
+    let tag = variant.variant_tag.clone().unwrap_or_default();
+    let variant_key = block.variant_section_key(&tag).unwrap_or_default();
+    let rest = variant.key.strip_prefix(&variant_key).unwrap_or_default();
+    // serde_yaml reads an externally tagged variant from a YAML tag, `mode: !Tcp`
+    if block.meta.tagging == Tagging::External {
+        section.key = block.key.clone() + rest;
+        let depth = util::key_split(&block.key).len();
+        return render_section(writer, &section, Some((depth, &tag)));
+    }
+    section.key = block.variant_content_key(&tag) + rest;
+    if !rest.is_empty() || !block.meta.tagging.has_tag_key() {
+        return render_section(writer, &section, None);
+    }
+    let commented = !selected || block.is_comented();
+    let mut part = Part {
+        key: &block.key,
+        tag: None,
+        map_depth: 0,
+        empty: None,
+        is_array: false,
+        array_index: section.array_index,
+        commented,
+        comment: String::new(),
+        lines: block.render_variant_tag_as(Syntax::Yaml, BANG_COMMENT, &tag, commented, selected),
+    };
+    if !section.meta.config.is_comment_hidden() {
+        part.comment = section.comment().render()?;
+    }
+    let mut blocks = Vec::new();
+    for block in &section.blocks {
+        blocks.push(block.render_as(Syntax::Yaml, BANG_COMMENT)?);
+    }
+    if section.key == block.key {
+        part.lines.append(&mut blocks);
+        writer.section(part);
+    } else {
+        writer.section(part);
+        writer.section(Part {
+            key: &section.key,
+            tag: None,
+            map_depth: 0,
+            empty: None,
+            is_array: false,
+            array_index: section.array_index,
+            commented,
+            comment: String::new(),
+            lines: blocks,
+        });
+    }
+    Ok(())
+}
+
+struct Part<'a> {
+    key: &'a str,
+    // the variant tag written on the header at this depth
+    tag: Option<(usize, &'a str)>,
+    // the map entry idents at the end of the key, the docs go above the map
+    map_depth: usize,
+    // a commented array or map still writes its header up to this depth as `[]`
+    // or `{}`, serde requires the field
+    empty: Option<(usize, &'static str)>,
+    is_array: bool,
+    array_index: Option<usize>,
+    commented: bool,
+    comment: String,
+    lines: Vec<String>,
+}
+
+// dotted section keys become nested mappings, a mapping stays open while the
+// following sections are below it so no key is written twice
+#[derive(Default)]
+struct Writer {
+    lines: Vec<String>,
+    open: Vec<(String, Option<usize>)>,
+    headers: Vec<usize>,
+    separated: bool,
+}
+
+impl Writer {
+    fn separate(&mut self) {
+        self.separated = true;
+    }
+
+    fn push(&mut self, line: String) {
+        if self.separated && !self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.separated = false;
+        self.lines.push(line);
+    }
+
+    fn header(&mut self, line: String) {
+        self.push(line);
+        self.headers.push(self.lines.len() - 1);
+    }
+
+    fn section(&mut self, part: Part) {
+        let idents: Vec<_> = if part.key == ROOT_KEY {
+            Vec::new()
+        } else {
+            util::key_split(part.key)
+        };
+        let depth = idents.len();
+        let by_name = self
+            .open
+            .iter()
+            .zip(&idents)
+            .take_while(|((open, _), ident)| open == *ident)
+            .count();
+        let mut shared = self
+            .open
+            .iter()
+            .zip(&idents)
+            .take_while(|((open, index), ident)| {
+                open == *ident && (index.is_none() || *index == part.array_index)
+            })
+            .count();
+        let element = part.is_array && part.array_index.is_some() && shared < depth;
+        let reopened = element && by_name == depth;
+        let comment_at = depth.saturating_sub(part.map_depth);
+        let (active, empty) = match part.empty {
+            // only below open mappings, an empty value under a commented one is wrong
+            Some((active, empty)) if part.commented && shared + 1 >= active => (active, empty),
+            _ if part.commented => (0, ""),
+            _ => (depth, ""),
+        };
+        // a commented section always repeats its own header
+        if active < depth {
+            shared = shared.min(depth.saturating_sub(1));
+        }
+        if active > 0 {
+            self.open.truncate(shared);
+        }
+        // an array item takes one more level for its `- ` marker
+        let mut level: usize = self.open[..shared]
+            .iter()
+            .map(|(_, index)| 1 + usize::from(index.is_some()))
+            .sum();
+        for (i, ident) in idents.iter().enumerate().skip(shared) {
+            let item = element && i + 1 == depth;
+            if !(item && reopened) {
+                if i + 1 == comment_at {
+                    self.comment(&part.comment, level);
+                }
+                let indent = INDENT.repeat(level);
+                let bang = if i < active { "" } else { BANG_COMMENT };
+                let ident = util::key_unquote(ident);
+                let tag = match part.tag {
+                    Some((depth, tag)) if depth == i + 1 => format!(" !{tag}"),
+                    _ => String::new(),
+                };
+                let line = format!("{indent}{bang}{}:{tag}", yaml_key(&ident));
+                if i + 1 == active && !empty.is_empty() {
+                    self.push(format!("{line} {empty}"));
+                } else {
+                    self.header(line);
+                }
+            }
+            level += 1 + usize::from(item);
+            if i < active {
+                let index = if item { part.array_index } else { None };
+                self.open.push((ident.to_string(), index));
+            }
+        }
+        if depth == 0 {
+            self.comment(&part.comment, 0);
+            if !part.comment.trim().is_empty() {
+                self.separate();
+            }
+        } else if shared == depth && part.map_depth == 0 {
+            self.comment(&part.comment, level - 1);
+        }
+        let bang = if part.commented { BANG_COMMENT } else { "" };
+        let indent = INDENT.repeat(level);
+        let outer = INDENT.repeat(level.saturating_sub(1));
+        let mut item = element;
+        for text in &part.lines {
+            for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                let active = !line.starts_with(COMMENT);
+                let marked = active || (part.commented && line.starts_with(BANG_COMMENT));
+                if item && marked {
+                    match line.strip_prefix(BANG_COMMENT) {
+                        Some(rest) => self.push(format!("{outer}{BANG_COMMENT}{ITEM}{rest}")),
+                        None => self.push(format!("{outer}{ITEM}{line}")),
+                    }
+                    item = false;
+                } else if item {
+                    self.push(format!("{outer}{line}"));
+                } else {
+                    self.push(format!("{indent}{line}"));
+                }
+            }
+        }
+        if item {
+            self.header(format!("{outer}{bang}{}", ITEM.trim_end()));
+        }
+    }
+
+    fn comment(&mut self, comment: &str, level: usize) {
+        let indent = INDENT.repeat(level);
+        for line in comment.lines().filter(|line| !line.trim().is_empty()) {
+            self.push(format!("{indent}{line}"));
+        }
+    }
+
+    // an active mapping without any active entry would read as null
+    fn finish(mut self) -> String {
+        for &index in &self.headers {
+            let header = &self.lines[index];
+            if header.trim_start().starts_with(COMMENT) {
+                continue;
+            }
+            let width = indent_width(header);
+            let next = self.lines[index + 1..]
+                .iter()
+                .find(|line| !line.trim().is_empty() && !line.trim_start().starts_with(COMMENT));
+            if next.is_none_or(|line| indent_width(line) <= width) {
+                self.lines[index].push_str(" {}");
+            }
+        }
+        self.lines.join("\n").trim().to_string()
+    }
+}
+
+// the header depth and value of an array or map field without entries
+fn empty_collection(section: &Section) -> Option<(usize, &'static str)> {
+    let optional = section.meta.is_option_type()
+        || section
+            .blocks
+            .iter()
+            .any(|block| block.meta.is_option_type());
+    if optional || section.variant_tag.is_some() || !section.is_commented() {
+        return None;
+    }
+    let idents = util::key_split(&section.key);
+    if let Some(depth) = idents.iter().position(|ident| *ident == MAP_KEY) {
+        return (depth > 0).then_some((depth, "{}"));
+    }
+    if section.key == ROOT_KEY {
+        return None;
+    }
+    if section.meta.is_array {
+        return Some((idents.len(), "[]"));
+    }
+    let template = !section.blocks.is_empty() && section.blocks.iter().all(Block::is_template);
+    template.then_some((idents.len(), "{}"))
+}
+
+fn indent_width(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn yaml_key(key: &str) -> String {
+    let plain = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && !key.starts_with('-')
+        && key.parse::<f64>().is_err()
+        && !matches!(
+            key.to_lowercase().as_str(),
+            "true" | "false" | "null" | "yes" | "no" | "on" | "off"
+        );
+    if plain {
+        key.to_string()
+    } else {
+        yaml_string(key)
+    }
+}
+
+fn yaml_value(value: &TomlValue) -> String {
+    match value {
+        TomlValue::String(text) => yaml_string(text),
+        TomlValue::Float(number) if number.is_nan() => ".nan".to_string(),
+        TomlValue::Float(number) if number.is_infinite() => {
+            if number.is_sign_negative() {
+                "-.inf".to_string()
+            } else {
+                ".inf".to_string()
+            }
+        }
+        TomlValue::Integer(_) | TomlValue::Float(_) | TomlValue::Boolean(_) => value.to_string(),
+        TomlValue::Datetime(datetime) => yaml_string(&datetime.to_string()),
+        TomlValue::Array(values) => {
+            let values: Vec<_> = values.iter().map(yaml_value).collect();
+            format!("[{}]", values.join(", "))
+        }
+        TomlValue::Table(table) => {
+            let entries: Vec<_> = table
+                .iter()
+                .map(|(key, value)| format!("{}: {}", yaml_key(key), yaml_value(value)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+    }
+}
+
+fn yaml_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use toml_input::TomlInput;
+use toml_input::{TomlContent, TomlInput};
 
 #[test]
 fn test_skip_none() {
@@ -83,3 +83,476 @@ fn test_comment_style() {
     let test1: Test = toml::from_str(&text).unwrap();
     assert_eq!(test, test1);
 }
+
+#[test]
+fn test_comment_style_attribute() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[toml_input(comment_style = "hide")]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `b`
+        #[toml_input(comment_style = "show")]
+        b: usize,
+        /// comment `c`
+        c: TestEnum,
+        /// comment `inner`
+        inner: Inner,
+    }
+    /// comment `TestEnum`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[toml_input(comment_style = "show")]
+    enum TestEnum {
+        /// comment `A`
+        #[default]
+        A,
+        /// comment `B`
+        #[toml_input(comment_style = "hide")]
+        B,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `d`
+        d: i32,
+    }
+    let res = Test::schema_to_string().unwrap();
+    let text = r#"a = 0
+# comment `b`
+b = 0
+# comment `A`
+c = "A"
+#!c = "B"
+
+[inner]
+d = 0"#
+        .to_string();
+    assert_eq!(res, text);
+    let _: Test = toml::from_str(&text).unwrap();
+}
+
+#[test]
+fn test_commented() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `b`
+        #[toml_input(commented)]
+        b: usize,
+        /// comment `inner`
+        inner: Inner,
+        /// comment `c`
+        c: Option<TestEnum>,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    #[toml_input(commented)]
+    struct Inner {
+        /// comment `d`
+        d: i32,
+        /// comment `e`
+        #[toml_input(commented = false)]
+        e: i32,
+    }
+    /// comment `TestEnum`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum TestEnum {
+        /// comment `A`
+        #[default]
+        #[toml_input(commented)]
+        A,
+        /// comment `B`
+        B,
+    }
+    let res = Test::schema_to_string().unwrap();
+    let text = r#"# comment `Test`
+
+# comment `a`
+a = 0
+# comment `b`
+#!b = 0
+# comment `A`
+#!c = "A"
+# comment `B`
+#!c = "B"
+
+# comment `inner`
+[inner]
+# comment `d`
+#!d = 0
+# comment `e`
+e = 0"#
+        .to_string();
+    assert_eq!(res, text);
+    let test: Test = toml::from_str(&text).unwrap();
+    assert_eq!(test.b, 0);
+
+    let mut content = TomlContent::new(Test::schema().unwrap().flatten());
+    content.config_commented(false);
+    let res = content.render().unwrap();
+    let text = r#"# comment `Test`
+
+# comment `a`
+a = 0
+# comment `b`
+b = 0
+# comment `A`
+#!c = "A"
+# comment `B`
+#!c = "B"
+
+# comment `inner`
+[inner]
+# comment `d`
+d = 0
+# comment `e`
+e = 0"#;
+    assert_eq!(res, text);
+}
+
+#[test]
+fn test_render_options() {
+    use toml_input::config::{BlankLines, CommentStyle, EnumStyle, KeyOrder, RenderOptions};
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `b`
+        #[toml_input(comment_style = "show")]
+        b: Option<usize>,
+        /// comment `a`
+        a: TestEnum,
+        /// comment `inner`
+        inner: Inner,
+    }
+    /// comment `TestEnum`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum TestEnum {
+        /// comment `A`
+        #[default]
+        A,
+        /// comment `B`
+        B,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `d`
+        d: i32,
+    }
+    let test = Test::default();
+    let options = RenderOptions {
+        comment_style: Some(CommentStyle::Hide),
+        bang_comment: "# ".to_string(),
+        blank_lines: BlankLines::None,
+        enum_style: Some(EnumStyle::Fold),
+        key_order: KeyOrder::Sorted,
+        ..Default::default()
+    };
+    let res = test.clone().into_string_with(&options).unwrap();
+    let text = r#"# a = "A" | "B"
+a = "A"
+# comment `b`
+# b = 0
+[inner]
+d = 0"#;
+    assert_eq!(res, text);
+
+    let res = test
+        .clone()
+        .into_string_with(&RenderOptions::default())
+        .unwrap();
+    assert_eq!(res, test.clone().into_string().unwrap());
+
+    let options = RenderOptions {
+        schema_path: Some("./config.schema.json".to_string()),
+        ..Default::default()
+    };
+    let res = test.clone().into_string_with(&options).unwrap();
+    let text = format!(
+        "#:schema ./config.schema.json\n\n{}",
+        test.into_string().unwrap()
+    );
+    assert_eq!(res, text);
+}
+
+#[test]
+fn test_minimal() {
+    use std::collections::BTreeMap;
+    use toml_input::config::RenderOptions;
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `b`
+        b: i32,
+        /// comment `c`
+        c: Option<String>,
+        /// comment `inner`
+        inner: Inner,
+        /// comment `mode`
+        mode: Mode,
+        /// comment `items`
+        items: Vec<Inner>,
+        /// comment `map`
+        map: BTreeMap<String, i32>,
+        /// comment `opt`
+        opt: Option<u32>,
+        /// comment `inner_opt`
+        inner_opt: Option<Inner>,
+        /// comment `speed`
+        speed: Speed,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `d`
+        d: i32,
+    }
+    /// comment `Mode`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
+    enum Mode {
+        /// comment `Tcp`
+        Tcp {
+            /// comment `port`
+            port: u16,
+        },
+        /// comment `Udp`
+        Udp,
+    }
+    impl Default for Mode {
+        fn default() -> Self {
+            Mode::Tcp { port: 0 }
+        }
+    }
+    /// comment `Speed`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum Speed {
+        /// comment `Fast`
+        #[default]
+        Fast,
+        /// comment `Slow`
+        Slow {
+            /// comment `delay`
+            delay: u32,
+        },
+    }
+    let options = RenderOptions {
+        minimal: true,
+        ..Default::default()
+    };
+    let res = Test::default().into_string_with(&options).unwrap();
+    assert_eq!(res, "");
+
+    let test = Test {
+        b: 2,
+        mode: Mode::Tcp { port: 80 },
+        items: vec![Inner::default()],
+        map: BTreeMap::from([("x".to_string(), 0)]),
+        ..Default::default()
+    };
+    let res = test.clone().into_string_with(&options).unwrap();
+    let text = r#"# comment `Test`
+
+# comment `b`
+b = 2
+# comment `Udp`
+#!mode = "Udp"
+
+# comment `Tcp`
+[mode.Tcp]
+# comment `port`
+port = 80
+
+# comment `items`
+[[items]]
+# comment `d`
+d = 0
+
+# comment `map`
+[map]
+x = 0"#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(&res).unwrap();
+    assert_eq!(test, test1);
+
+    let test = Test {
+        opt: Some(0),
+        inner_opt: Some(Inner::default()),
+        speed: Speed::Slow { delay: 0 },
+        ..Default::default()
+    };
+    let res = test.clone().into_string_with(&options).unwrap();
+    let text = r#"# comment `Test`
+
+# comment `opt`
+opt = 0
+# comment `Fast`
+#!speed = "Fast"
+
+# comment `inner_opt`
+[inner_opt]
+# comment `d`
+d = 0
+
+# comment `Slow`
+[speed.Slow]
+# comment `delay`
+delay = 0"#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(&res).unwrap();
+    assert_eq!(test, test1);
+}
+
+#[test]
+fn test_minimal_variant_default() {
+    use toml_input::config::RenderOptions;
+    /// comment `Cfg`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct Cfg {
+        /// comment `backend`
+        backend: Backend,
+        /// comment `mode`
+        mode: Mode,
+    }
+    /// comment `Backend`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[serde(tag = "type")]
+    enum Backend {
+        /// comment `Local`
+        Local {
+            /// comment `path`
+            path: String,
+        },
+        /// comment `Remote`
+        Remote {
+            /// comment `url`
+            url: String,
+        },
+    }
+    impl Default for Backend {
+        fn default() -> Self {
+            Backend::Local {
+                path: "/tmp".to_string(),
+            }
+        }
+    }
+    /// comment `Mode`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
+    enum Mode {
+        /// comment `Tcp`
+        Tcp {
+            /// comment `port`
+            port: u16,
+        },
+        /// comment `Udp`
+        Udp,
+    }
+    impl Default for Mode {
+        fn default() -> Self {
+            Mode::Tcp { port: 22 }
+        }
+    }
+    let options = RenderOptions {
+        minimal: true,
+        ..Default::default()
+    };
+    let res = Cfg::default().into_string_with(&options).unwrap();
+    assert_eq!(res, "");
+
+    let cfg = Cfg {
+        mode: Mode::Tcp { port: 23 },
+        ..Default::default()
+    };
+    let res = cfg.clone().into_string_with(&options).unwrap();
+    println!("{res}");
+    let text = r#"# comment `Cfg`
+
+# comment `Udp`
+#!mode = "Udp"
+
+# comment `Tcp`
+[mode.Tcp]
+# comment `port`
+port = 23"#;
+    assert_eq!(res, text);
+    let cfg1: Cfg = toml::from_str(&res).unwrap();
+    assert_eq!(cfg, cfg1);
+}
+
+#[test]
+fn test_annotate() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[toml_input(comment_style = "annotate")]
+    struct Test {
+        /// comment `port`
+        #[toml_input(inner_default = "8080")]
+        port: Option<u32>,
+        /// comment `timeout`
+        #[toml_input(type_name = "duration")]
+        timeout: String,
+        /// comment `hosts`
+        hosts: Vec<String>,
+        /// comment `mode`
+        mode: Mode,
+        /// comment `inner`
+        inner: Option<Inner>,
+    }
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum Mode {
+        /// comment `Fast`
+        Fast,
+        /// comment `Slow`
+        #[default]
+        Slow,
+    }
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `d`
+        d: i32,
+    }
+    let test = Test {
+        port: None,
+        timeout: "5s".to_string(),
+        hosts: vec!["a".to_string()],
+        mode: Mode::Fast,
+        inner: Some(Inner { d: 1 }),
+    };
+    let res = test.clone().into_string().unwrap();
+    let text = r#"# comment `Test`
+
+# comment `port`
+# type: u32 (optional), default: 8080
+#!port = 8080
+# comment `timeout`
+# type: duration, default: ""
+timeout = "5s"
+# comment `hosts`
+# type: [string]
+hosts = ["a"]
+# type: Mode, default: "Slow"
+# comment `Fast`
+mode = "Fast"
+# comment `Slow`
+#!mode = "Slow"
+
+# comment `inner`
+# type: Inner (optional)
+[inner]
+# comment `d`
+# type: i32, default: 0
+d = 1"#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(text).unwrap();
+    assert_eq!(test, test1);
+    let markdown = Test::schema().unwrap().to_markdown();
+    assert!(markdown.contains("| `timeout` | `duration` |"));
+}
@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use strum_macros::{AsRefStr, EnumIter};
 use toml_input::TomlInput;
 
 #[test]
@@ -14,7 +13,7 @@ fn test_schema() {
     }
     /// comment `TestEnum`
     #[allow(dead_code)]
-    #[derive(Debug, EnumIter, AsRefStr, TomlInput, Serialize, Deserialize, Default)]
+    #[derive(Debug, TomlInput, Serialize, Deserialize, Default)]
     enum TestEnum {
         /// comment `A`
         A,
@@ -49,9 +48,7 @@ fn test_value() {
     }
     /// comment `TestEnum`
     #[allow(dead_code)]
-    #[derive(
-        Debug, Clone, EnumIter, AsRefStr, TomlInput, Serialize, PartialEq, Deserialize, Default,
-    )]
+    #[derive(Debug, Clone, TomlInput, Serialize, PartialEq, Deserialize, Default)]
     enum TestEnum {
         /// comment `A`
         A,
@@ -91,7 +88,7 @@ fn test_single() {
         b: TestEnum,
     }
     /// comment `TestEnum`
-    #[derive(Debug, Clone, EnumIter, AsRefStr, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
     #[allow(dead_code)]
     #[derive(Default)]
     enum TestEnum {
@@ -131,7 +128,7 @@ fn test_fold() {
         b: TestEnum,
     }
     /// comment `TestEnum`
-    #[derive(Debug, Clone, EnumIter, AsRefStr, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
     #[allow(dead_code)]
     #[derive(Default)]
     enum TestEnum {
@@ -171,7 +168,7 @@ fn test_tuple() {
         b: TestEnum,
     }
     /// comment `TestEnum`
-    #[derive(Debug, Clone, EnumIter, AsRefStr, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
     #[allow(dead_code)]
     enum TestEnum {
         /// comment `A`
@@ -214,7 +211,7 @@ fn test_struct() {
         b: TestEnum,
     }
     /// comment `TestEnum`
-    #[derive(Debug, Clone, EnumIter, AsRefStr, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
     #[allow(dead_code)]
     #[derive(Default)]
     enum TestEnum {
@@ -222,9 +219,42 @@ fn test_struct() {
         #[default]
         A,
         /// comment `B`
-        B { c: usize, d: f64 },
+        B {
+            /// comment `c`
+            c: usize,
+            /// comment `d`
+            d: f64,
+        },
+        /// comment `C`
+        C {
+            /// comment `e`
+            e: String,
+        },
     }
 
+    let text = Test::schema_to_string().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `a`
+a = 0
+# comment `A`
+b = "A"
+
+# comment `B`
+#![b.B]
+# comment `c`
+#!c = 0
+# comment `d`
+#!d = 0.0
+
+# comment `C`
+#![b.C]
+# comment `e`
+#!e = """#;
+    assert_eq!(res, text);
+    let _: Test = toml::from_str(res).unwrap();
+
     let test = Test {
         a: 0,
         b: TestEnum::B { c: 2, d: 1.5 },
@@ -237,8 +267,484 @@ fn test_struct() {
 a = 0
 # comment `A`
 #!b = "A"
+
 # comment `B`
-b = { B = { c = 2, d = 1.5 } }"#;
+[b.B]
+# comment `c`
+c = 2
+# comment `d`
+d = 1.5
+
+# comment `C`
+#![b.C]
+# comment `e`
+#!e = """#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(res).unwrap();
+    assert_eq!(test, test1);
+}
+
+#[test]
+fn test_newtype_table() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `backend`
+        #[toml_input(enum_style = "single")]
+        backend: Backend,
+    }
+    /// comment `S3`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct S3 {
+        /// comment `bucket`
+        bucket: String,
+    }
+    /// comment `Backend`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[allow(dead_code)]
+    #[derive(Default)]
+    enum Backend {
+        /// comment `Local`
+        #[default]
+        Local,
+        /// comment `S3` variant
+        S3(S3),
+    }
+
+    let test = Test {
+        backend: Backend::S3(S3 {
+            bucket: "data".to_string(),
+        }),
+    };
+    let text = test.clone().into_string().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `S3` variant
+[backend.S3]
+# comment `bucket`
+bucket = "data""#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(res).unwrap();
+    assert_eq!(test, test1);
+}
+
+#[test]
+fn test_internally_tagged() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `backend`
+        backend: Backend,
+    }
+    /// comment `Backend`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[serde(tag = "type")]
+    #[allow(dead_code)]
+    #[derive(Default)]
+    enum Backend {
+        /// comment `Local`
+        #[default]
+        Local,
+        /// comment `S3`
+        S3 {
+            /// comment `bucket`
+            bucket: String,
+        },
+    }
+
+    let text = Test::schema_to_string().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `a`
+a = 0
+
+# comment `Local`
+[backend]
+type = "Local"
+
+# comment `S3`
+#![backend]
+#!type = "S3"
+# comment `bucket`
+#!bucket = """#;
+    assert_eq!(res, text);
+    let test: Test = toml::from_str(res).unwrap();
+    assert_eq!(test, Test::default());
+
+    let test = Test {
+        a: 1,
+        backend: Backend::S3 {
+            bucket: "data".to_string(),
+        },
+    };
+    let text = test.clone().into_string().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `a`
+a = 1
+
+# comment `Local`
+#![backend]
+#!type = "Local"
+
+# comment `S3`
+[backend]
+type = "S3"
+# comment `bucket`
+bucket = "data""#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(res).unwrap();
+    assert_eq!(test, test1);
+}
+
+#[test]
+fn test_adjacently_tagged() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `backend`
+        backend: Backend,
+    }
+    /// comment `Backend`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[serde(tag = "t", content = "c")]
+    #[allow(dead_code)]
+    #[derive(Default)]
+    enum Backend {
+        /// comment `Local`
+        #[default]
+        Local,
+        /// comment `Named`
+        Named(String),
+        /// comment `S3`
+        S3 {
+            /// comment `bucket`
+            bucket: String,
+        },
+    }
+
+    let test = Test {
+        backend: Backend::S3 {
+            bucket: "data".to_string(),
+        },
+    };
+    let text = test.clone().into_string().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `Local`
+#![backend]
+#!t = "Local"
+
+# comment `Named`
+#![backend]
+#!t = "Named"
+#!c = ""
+
+# comment `S3`
+[backend]
+t = "S3"
+[backend.c]
+# comment `bucket`
+bucket = "data""#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(res).unwrap();
+    assert_eq!(test, test1);
+
+    let test = Test {
+        backend: Backend::Named("name".to_string()),
+    };
+    let text = test.clone().into_string().unwrap();
+    let test1: Test = toml::from_str(&text).unwrap();
+    assert_eq!(test, test1);
+}
+
+#[test]
+fn test_untagged() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `backend`
+        backend: Backend,
+    }
+    /// comment `Backend`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[serde(untagged)]
+    #[allow(dead_code)]
+    enum Backend {
+        /// comment `Path`
+        Path(String),
+        /// comment `S3`
+        S3 {
+            /// comment `bucket`
+            bucket: String,
+        },
+    }
+    impl Default for Backend {
+        fn default() -> Self {
+            Backend::Path("/tmp".to_string())
+        }
+    }
+
+    let test = Test {
+        backend: Backend::S3 {
+            bucket: "data".to_string(),
+        },
+    };
+    let text = test.clone().into_string().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `Path`
+#!backend = ""
+
+# comment `S3`
+[backend]
+# comment `bucket`
+bucket = "data""#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(res).unwrap();
+    assert_eq!(test, test1);
+}
+
+#[test]
+fn test_default_payload() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `b`
+        b: TestEnum,
+    }
+    /// comment `TestEnum`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[allow(dead_code)]
+    enum TestEnum {
+        /// comment `A`
+        A(u8, String),
+        /// comment `B`
+        #[default]
+        B,
+    }
+
+    let text = Test::schema_to_string().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `A`
+#!b = { A = [0, ""] }
+# comment `B`
+b = "B""#;
+    assert_eq!(res, text);
+    let test: Test = toml::from_str(res).unwrap();
+    assert_eq!(test, Test::default());
+}
+
+#[test]
+fn test_data_default() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `mode`
+        mode: Mode,
+    }
+    /// comment `Mode`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[allow(dead_code)]
+    enum Mode {
+        /// comment `Tcp`
+        Tcp {
+            /// comment `port`
+            port: u16,
+        },
+        /// comment `Unix`
+        Unix {
+            /// comment `path`
+            path: String,
+        },
+    }
+    impl Default for Mode {
+        fn default() -> Self {
+            Mode::Tcp { port: 22 }
+        }
+    }
+
+    let text = Test::schema_to_string().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `Tcp`
+[mode.Tcp]
+# comment `port`
+port = 22
+
+# comment `Unix`
+#![mode.Unix]
+# comment `path`
+#!path = """#;
+    assert_eq!(res, text);
+    let test: Test = toml::from_str(res).unwrap();
+    assert_eq!(test, Test::default());
+    assert_eq!(Test::default().into_string().unwrap(), res);
+}
+
+#[test]
+fn test_rename() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `level`
+        level: Level,
+        /// comment `backend`
+        backend: Backend,
+    }
+    /// comment `Level`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(rename_all = "kebab-case")]
+    #[allow(dead_code)]
+    enum Level {
+        /// comment `WarnOnly`
+        WarnOnly,
+        /// comment `ErrorOnly`
+        #[default]
+        #[serde(rename = "error")]
+        ErrorOnly,
+    }
+    /// comment `Backend`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(rename_all = "snake_case")]
+    #[allow(dead_code)]
+    enum Backend {
+        /// comment `LocalDisk`
+        #[default]
+        LocalDisk,
+        /// comment `S3Bucket`
+        S3Bucket {
+            /// comment `name`
+            name: String,
+        },
+    }
+
+    let test = Test {
+        level: Level::WarnOnly,
+        backend: Backend::S3Bucket {
+            name: "data".to_string(),
+        },
+    };
+    let text = test.clone().into_string().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `WarnOnly`
+level = "warn-only"
+# comment `ErrorOnly`
+#!level = "error"
+# comment `LocalDisk`
+#!backend = "local_disk"
+
+# comment `S3Bucket`
+[backend.s3_bucket]
+# comment `name`
+name = "data""#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(res).unwrap();
+    assert_eq!(test, test1);
+}
+
+#[test]
+fn test_flex() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, Default, PartialEq)]
+    #[toml_input(flex_limit = 2)]
+    struct Test {
+        /// comment `a`
+        #[toml_input(enum_style = "flex(3)")]
+        a: TestEnum,
+        /// comment `b`
+        b: TestEnum,
+    }
+    /// comment `TestEnum`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum TestEnum {
+        /// comment `A`
+        #[default]
+        A,
+        /// comment `B`
+        B,
+        /// comment `C`
+        C,
+    }
+
+    let test = Test {
+        a: TestEnum::B,
+        b: TestEnum::C,
+    };
+    let mut content = test.clone().into_content().unwrap();
+    let text = content.render().unwrap();
+    let res = r#"# comment `Test`
+
+# comment `A`
+#!a = "A"
+# comment `B`
+a = "B"
+# comment `C`
+#!a = "C"
+# comment `b`
+# b = "A" | "B" | "C"
+b = "C""#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(res).unwrap();
+    assert_eq!(test, test1);
+
+    content.config_flex_limit(4);
+    assert_eq!(res, content.render().unwrap());
+}
+
+#[test]
+fn test_flex_render_limit() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, Default, PartialEq)]
+    struct Test {
+        /// comment `a`
+        #[toml_input(enum_style = "flex(3)")]
+        a: TestEnum,
+        /// comment `b`
+        b: TestEnum,
+    }
+    /// comment `TestEnum`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum TestEnum {
+        /// comment `A`
+        #[default]
+        A,
+        /// comment `B`
+        B,
+        /// comment `C`
+        C,
+    }
+
+    let test = Test {
+        a: TestEnum::B,
+        b: TestEnum::C,
+    };
+    let mut content = test.clone().into_content().unwrap();
+    content.config_flex_limit(2);
+    let text = content.render().unwrap();
+    let res = r#"# comment `Test`
+
+# comment `A`
+#!a = "A"
+# comment `B`
+a = "B"
+# comment `C`
+#!a = "C"
+# comment `b`
+# b = "A" | "B" | "C"
+b = "C""#;
     assert_eq!(res, text);
     let test1: Test = toml::from_str(res).unwrap();
     assert_eq!(test, test1);
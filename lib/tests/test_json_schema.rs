@@ -0,0 +1,222 @@
+#![cfg(feature = "json-schema")]
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use toml_input::TomlInput;
+
+#[test]
+fn test_json_schema() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `b`
+        b: TestEnum,
+        /// comment `c`
+        c: Option<Inner>,
+        /// comment `d`
+        d: Vec<u8>,
+        /// comment `e`
+        e: BTreeMap<String, Inner>,
+    }
+    /// comment `TestEnum`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum TestEnum {
+        /// comment `A`
+        #[default]
+        A,
+        /// comment `B`
+        B,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `f`
+        f: String,
+    }
+    let inner = json!({
+        "type": "object",
+        "description": "comment `Inner`",
+        "properties": {
+            "f": {"type": "string", "description": "comment `f`", "default": ""},
+        },
+        "required": ["f"],
+        "additionalProperties": false,
+        "default": {"f": ""},
+    });
+    let mut inner_c = inner.clone();
+    inner_c["description"] = json!("comment `c`");
+    inner_c.as_object_mut().unwrap().remove("default");
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Test",
+        "description": "comment `Test`",
+        "type": "object",
+        "properties": {
+            "a": {"type": "integer", "description": "comment `a`", "default": 0},
+            "b": {"enum": ["A", "B"], "description": "comment `b`", "default": "A"},
+            "c": inner_c,
+            "d": {
+                "type": "array",
+                "items": {"type": "integer", "minimum": 0, "default": 0},
+                "description": "comment `d`",
+            },
+            "e": {
+                "type": "object",
+                "additionalProperties": inner,
+                "description": "comment `e`",
+            },
+        },
+        "required": ["a", "b", "d", "e"],
+        "additionalProperties": false,
+        "default": {"a": 0, "b": "A", "d": [], "e": {}},
+    });
+    assert_eq!(Test::schema().unwrap().to_json_schema(), schema);
+}
+
+#[test]
+fn test_json_schema_variant() {
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        backend: Backend,
+    }
+    /// comment `Backend`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(tag = "kind")]
+    enum Backend {
+        /// comment `Memory`
+        #[default]
+        Memory,
+        /// comment `Disk`
+        Disk { path: String },
+    }
+    let schema = Test::schema().unwrap().to_json_schema();
+    let backend = json!({
+        "description": "comment `Backend`",
+        "default": {"kind": "Memory"},
+        "oneOf": [
+            {
+                "type": "object",
+                "properties": {"kind": {"const": "Memory"}},
+                "required": ["kind"],
+                "additionalProperties": false,
+                "description": "comment `Memory`",
+            },
+            {
+                "type": "object",
+                "properties": {
+                    "kind": {"const": "Disk"},
+                    "path": {"type": "string", "default": ""},
+                },
+                "required": ["kind", "path"],
+                "additionalProperties": false,
+                "description": "comment `Disk`",
+            },
+        ],
+    });
+    assert_eq!(schema["properties"]["backend"], backend);
+}
+
+#[test]
+fn test_json_schema_default() {
+    fn default_port() -> u16 {
+        8080
+    }
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        name: String,
+        #[serde(default)]
+        verbose: bool,
+        #[serde(default = "default_port", alias = "p")]
+        port: u16,
+        inner: Inner,
+    }
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct Inner {
+        level: u8,
+    }
+    let schema = Test::schema().unwrap().to_json_schema();
+    assert_eq!(schema["required"], json!(["name", "inner"]));
+    assert_eq!(schema["properties"]["inner"].get("required"), None);
+    let port = json!({"type": "integer", "minimum": 0, "default": 8080});
+    assert_eq!(schema["properties"]["port"], port);
+    assert_eq!(schema["properties"]["p"], port);
+    let default = json!({"name": "", "verbose": false, "port": 8080, "inner": {"level": 0}});
+    assert_eq!(schema["default"], default);
+}
+
+#[test]
+fn test_taplo_schema() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `level`
+        #[toml_input(renamed_from = "log_level", since = "1.3")]
+        level: Level,
+        /// comment `inner`
+        inner: Inner,
+        #[toml_input(removed)]
+        legacy: Option<bool>,
+    }
+    /// comment `Level`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum Level {
+        /// comment `Info`
+        #[default]
+        Info,
+        /// comment `Debug`
+        Debug,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `d`
+        d: Option<i32>,
+    }
+    let schema = Test::schema().unwrap();
+    assert_eq!(
+        schema.to_json_schema()["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        vec!["inner", "level"]
+    );
+    let taplo = schema.to_taplo_schema(Some("CONFIG.md"));
+    assert_eq!(
+        taplo["x-taplo"],
+        json!({"initKeys": ["level", "inner"], "links": {"key": "CONFIG.md#test"}})
+    );
+    let properties = &taplo["properties"];
+    assert_eq!(
+        properties["level"]["x-taplo"],
+        json!({
+            "docs": {
+                "enumValues": ["comment `Info`", "comment `Debug`"],
+                "defaultValue": "comment `Info`",
+            },
+            "links": {"key": "CONFIG.md#test"},
+        })
+    );
+    assert_eq!(
+        properties["inner"]["x-taplo"],
+        json!({"links": {"key": "CONFIG.md#inner"}})
+    );
+    assert_eq!(
+        properties["inner"]["properties"]["d"]["x-taplo"],
+        json!({"links": {"key": "CONFIG.md#inner"}})
+    );
+    assert_eq!(
+        properties["log_level"],
+        json!({
+            "description": "renamed to `level` in 1.3",
+            "deprecated": true,
+            "x-taplo": {"hidden": true},
+        })
+    );
+    assert_eq!(properties["legacy"]["x-taplo"], json!({"hidden": true}));
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use toml_input::TomlInput;
+
+#[test]
+fn test_markdown() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `b`
+        b: Option<String>,
+        /// comment `mode`
+        mode: Mode,
+        /// comment `inner`
+        inner: Inner,
+        /// comment `items`
+        items: Vec<Inner>,
+        /// comment `servers`
+        servers: BTreeMap<String, Inner>,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `d`
+        d: bool,
+    }
+    /// comment `Mode`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum Mode {
+        /// comment `Off`
+        #[default]
+        Off,
+        /// comment `Tcp`
+        Tcp {
+            /// comment `port`
+            port: u16,
+        },
+    }
+    let text = Test::schema().unwrap().to_markdown();
+    let res = r#"# Test
+
+comment `Test`
+
+| Key | Type | Default | Allowed values | Description |
+| --- | --- | --- | --- | --- |
+| `a` | `i32` | `0` |  | comment `a` |
+| `b` | `Option<string>` |  |  | comment `b` |
+| `mode` | `Mode` | `"Off"` | `"Off"`, [`Tcp`](#modetcp-tcp) | comment `mode` |
+| [`inner`](#inner) | `Inner` |  |  | comment `inner` |
+| [`items`](#items) | `Vec<Inner>` |  |  | comment `items` |
+| [`servers`](#serversname) | `BTreeMap<Inner>` |  |  | comment `servers` |
+
+## `[mode.Tcp]` (Tcp)
+
+comment `Tcp`
+
+| Key | Type | Default | Allowed values | Description |
+| --- | --- | --- | --- | --- |
+| `port` | `u16` | `0` |  | comment `port` |
+
+## `[inner]`
+
+comment `inner`
+
+| Key | Type | Default | Allowed values | Description |
+| --- | --- | --- | --- | --- |
+| `d` | `bool` | `false` |  | comment `d` |
+
+## `[[items]]`
+
+comment `items`
+
+| Key | Type | Default | Allowed values | Description |
+| --- | --- | --- | --- | --- |
+| `d` | `bool` | `false` |  | comment `d` |
+
+## `[servers.<name>]`
+
+comment `servers`
+
+| Key | Type | Default | Allowed values | Description |
+| --- | --- | --- | --- | --- |
+| `d` | `bool` | `false` |  | comment `d` |"#;
+    assert_eq!(text, res);
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use toml_input::{TomlInput, migrate::Change};
+
+#[test]
+fn test_migrate() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `level`
+        #[toml_input(renamed_from = "log_level", since = "1.3")]
+        level: i32,
+        /// comment `server`
+        server: Server,
+        #[toml_input(removed, since = "1.2")]
+        legacy: Option<bool>,
+    }
+    /// comment `Server`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Server {
+        /// comment `port`
+        #[toml_input(renamed_from = ".port")]
+        port: u16,
+        /// comment `host`
+        #[serde(default)]
+        #[toml_input(renamed_from = "addr")]
+        host: String,
+    }
+    let text = Test::default().into_string().unwrap();
+    let res = r#"# comment `Test`
+
+# comment `level`
+# renamed from `log_level` in 1.3
+level = 0
+
+# comment `server`
+[server]
+# comment `port`
+# renamed from `.port`
+port = 0
+# comment `host`
+# renamed from `server.addr`
+host = """#;
+    assert_eq!(res, text);
+    let test = Test {
+        legacy: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(test.into_string().unwrap(), res);
+
+    let old = r#"# the log level
+log_level = 2
+port = 8080
+legacy = true
+
+[server]
+"#;
+    let report = Test::migrate_str(old).unwrap();
+    assert_eq!(
+        report.changes,
+        vec![
+            Change::Renamed {
+                from: "port".to_string(),
+                to: "server.port".to_string(),
+                since: None,
+            },
+            Change::Renamed {
+                from: "log_level".to_string(),
+                to: "level".to_string(),
+                since: Some("1.3".to_string()),
+            },
+            Change::Removed {
+                key: "legacy".to_string(),
+                since: Some("1.2".to_string()),
+            },
+        ]
+    );
+    assert_eq!(
+        report.to_string(),
+        "renamed `port` to `server.port`
+renamed `log_level` to `level` (since 1.3)
+removed `legacy` (since 1.2)"
+    );
+    let migrated = r#"# the log level
+level = 2
+
+[server]
+port = 8080
+"#;
+    assert_eq!(report.text, migrated);
+    let test: Test = toml::from_str(&report.text).unwrap();
+    assert_eq!((test.level, test.server.port), (2, 8080));
+    assert!(!Test::migrate_str(migrated).unwrap().is_changed());
+}
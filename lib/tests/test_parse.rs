@@ -0,0 +1,338 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use toml_input::{TomlContent, TomlInput, config::RenderOptions};
+
+#[test]
+fn test_parse_alternative() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `b`
+        b: TestEnum,
+        /// comment `c`
+        c: Option<Inner>,
+    }
+    /// comment `TestEnum`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum TestEnum {
+        /// comment `A`
+        #[default]
+        A,
+        /// comment `B`
+        B,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `d`
+        d: i32,
+    }
+    let text = Test::default().into_string().unwrap();
+    let res = r#"# comment `Test`
+
+# comment `a`
+a = 0
+# comment `A`
+b = "A"
+# comment `B`
+#!b = "B"
+
+# comment `c`
+#![c]
+# comment `d`
+#!d = 0"#;
+    assert_eq!(res, text);
+
+    let edited = r#"# comment `Test`
+
+# comment `a`
+a = 3
+# comment `A`
+#!b = "A"
+# comment `B`
+b = "B"
+
+# comment `c`
+[c]
+# comment `d`
+d = 4"#;
+    let value = TomlContent::parse(Test::schema().unwrap(), edited).unwrap();
+    let mut content = Test::default().into_content().unwrap();
+    content.merge_value(value);
+    assert_eq!(edited, content.render().unwrap());
+    let test: Test = toml::from_str(edited).unwrap();
+    assert_eq!(test.into_string().unwrap(), edited);
+
+    let options = RenderOptions {
+        bang_comment: "# ".to_string(),
+        ..Default::default()
+    };
+    let text = Test::default().into_string_with(&options).unwrap();
+    let edited = text
+        .replace("b = \"A\"", "# b = \"A\"")
+        .replace("# b = \"B\"", "b = \"B\"");
+    let value = TomlContent::parse(Test::schema().unwrap(), &edited).unwrap();
+    let mut content = Test::default().into_content().unwrap();
+    content.merge_value(value);
+    assert_eq!(content.render_with(&options).unwrap(), edited);
+
+    /// comment `Marked`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Marked {
+        #[doc = "!kept as written"]
+        e: Option<i32>,
+    }
+    let text = Marked::default().into_string_with(&options).unwrap();
+    let res = r#"# comment `Marked`
+
+#!kept as written
+# e = 0"#;
+    assert_eq!(text, res);
+}
+
+#[test]
+fn test_parse_variant() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `backend`
+        backend: Backend,
+        /// comment `servers`
+        servers: BTreeMap<String, Server>,
+    }
+    /// comment `Backend`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum Backend {
+        /// comment `Local`
+        Local {
+            /// comment `path`
+            path: String,
+        },
+        /// comment `S3`
+        S3 {
+            /// comment `bucket`
+            bucket: String,
+        },
+    }
+    impl Default for Backend {
+        fn default() -> Self {
+            Backend::Local {
+                path: "/tmp".to_string(),
+            }
+        }
+    }
+    /// comment `Server`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Server {
+        /// comment `port`
+        port: u16,
+    }
+    let test = Test {
+        backend: Backend::S3 {
+            bucket: "data".to_string(),
+        },
+        servers: BTreeMap::from([("alpha".to_string(), Server { port: 80 })]),
+    };
+    let text = test.clone().into_string().unwrap();
+    let value = TomlContent::parse(Test::schema().unwrap(), &text).unwrap();
+    let mut content = Test::default().into_content().unwrap();
+    content.merge_value(value);
+    assert_eq!(text, content.render().unwrap());
+
+    let edited = text
+        .replace(
+            "#![backend]\n#!type = \"local\"",
+            "[backend]\ntype = \"local\"",
+        )
+        .replace("#!path = \"/tmp\"", "path = \"/data\"")
+        .replace("[backend]\ntype = \"s3\"", "#![backend]\n#!type = \"s3\"")
+        .replace("bucket = \"data\"", "#!bucket = \"\"");
+    let value = TomlContent::parse(Test::schema().unwrap(), &edited).unwrap();
+    let mut content = Test::default().into_content().unwrap();
+    content.merge_value(value);
+    let res = content.render().unwrap();
+    let test: Test = toml::from_str(&res).unwrap();
+    let backend = Backend::Local {
+        path: "/data".to_string(),
+    };
+    assert_eq!(test.backend, backend);
+    assert_eq!(test.servers["alpha"].port, 80);
+    assert!(res.contains("#!type = \"s3\""));
+}
+
+#[test]
+fn test_parse_error() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `b`
+        b: TestEnum,
+    }
+    /// comment `TestEnum`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum TestEnum {
+        #[default]
+        A,
+        B,
+    }
+    let schema = Test::schema().unwrap();
+    assert!(TomlContent::parse(schema.clone(), "a = 1\na = 2").is_err());
+    assert!(TomlContent::parse(schema, "b = \"C\"").is_err());
+}
+
+#[test]
+fn test_upgrade() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `b` added later
+        b: String,
+        /// comment `c`
+        c: Option<usize>,
+        /// comment `inner`
+        inner: Inner,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `d`
+        d: i32,
+        /// comment `e` added later
+        e: bool,
+    }
+    let existing = r#"a = 3
+old = "kept"
+
+[inner]
+d = 4
+legacy = [1, 2]
+"#;
+    let res = Test::upgrade_str(existing).unwrap();
+    let text = r#"# comment `Test`
+
+# comment `a`
+a = 3
+# comment `b` added later
+b = ""
+# comment `c`
+#!c = 0
+
+# comment `inner`
+[inner]
+# comment `d`
+d = 4
+# comment `e` added later
+e = false
+
+# Unrecognized keys
+#!inner.legacy = [1, 2]
+#!old = "kept""#;
+    assert_eq!(res, text);
+    let test: Test = toml::from_str(&res).unwrap();
+    assert_eq!(test.a, 3);
+    assert_eq!(test.inner.d, 4);
+}
+
+#[test]
+fn test_alias() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `level`
+        #[serde(alias = "lvl", alias = "lvl2")]
+        level: u8,
+    }
+    let res = Test::upgrade_str("lvl2 = 3").unwrap();
+    let text = r#"# comment `Test`
+
+# comment `level`
+level = 3"#;
+    assert_eq!(res, text);
+
+    let value = TomlContent::parse(Test::schema().unwrap(), "lvl = 4").unwrap();
+    let mut content = Test::default().into_content().unwrap();
+    assert!(content.merge_value(value).is_empty());
+    let test: Test = toml::from_str(&content.render().unwrap()).unwrap();
+    assert_eq!(test.level, 4);
+}
+
+#[test]
+fn test_unknown_keys() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `inner`
+        inner: Vec<Inner>,
+        #[serde(flatten)]
+        extra: toml::Table,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `d`
+        d: i32,
+    }
+    let mut test = Test::default();
+    test.extra
+        .insert("newer".to_string(), toml::Value::Boolean(true));
+    let mut deeper = test.clone();
+    let table = toml::Table::from_iter([("x".to_string(), toml::Value::Integer(1))]);
+    deeper
+        .extra
+        .insert("deep".to_string(), toml::Value::Table(table));
+    let (content, warnings) = deeper.into_content_with_warnings().unwrap();
+    assert!(warnings.is_empty());
+    assert!(content.render().unwrap().contains("deep = { x = 1 }\n"));
+    let text = test.clone().into_string().unwrap();
+    let res = r#"# comment `Test`
+
+# comment `a`
+a = 0
+newer = true
+
+# comment `inner`
+#![[inner]]
+# comment `d`
+#!d = 0"#;
+    assert_eq!(text, res);
+
+    let edited = r#"a = 1
+
+[[inner]]
+d = 2
+legacy = [1, 2]
+"#;
+    let value = TomlContent::parse(Test::schema().unwrap(), edited).unwrap();
+    let mut content = Test::default().into_content().unwrap();
+    let warnings = content.merge_value(value);
+    assert_eq!(
+        warnings,
+        vec!["unrecognized key `inner.legacy` kept in its section"]
+    );
+    let res = r#"# comment `Test`
+
+# comment `a`
+a = 1
+
+# comment `inner`
+[[inner]]
+# comment `d`
+d = 2
+legacy = [1, 2]"#;
+    assert_eq!(content.render().unwrap(), res);
+
+    let mut content = Test::default().into_content().unwrap();
+    let value = TomlContent::parse(Test::schema().unwrap(), "a = 1\nnewer = 2").unwrap();
+    assert!(content.merge_value(value).is_empty());
+    assert!(content.render().unwrap().contains("a = 1\nnewer = 2\n"));
+}
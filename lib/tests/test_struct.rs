@@ -1,5 +1,8 @@
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+
 use serde::{Deserialize, Serialize};
-use toml_input::TomlInput;
+use toml_input::{TomlContent, TomlInput};
 
 #[test]
 fn test_schema() {
@@ -282,3 +285,434 @@ b = 3"
     let test1: Test = toml::from_str(&text).unwrap();
     assert_eq!(test, test1);
 }
+
+#[test]
+fn test_map() {
+    /// comment `Server`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Server {
+        /// comment `host`
+        host: String,
+        /// comment `port`
+        port: u16,
+    }
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `servers`
+        servers: BTreeMap<String, Server>,
+        /// comment `labels`
+        labels: HashMap<String, String>,
+    }
+    let res = Test::schema_to_string().unwrap();
+    println!("{res}");
+    let text = r#"# comment `Test`
+
+# comment `a`
+a = 0
+
+# comment `servers`
+#![servers.<name>]
+# comment `host`
+#!host = ""
+# comment `port`
+#!port = 0
+
+# comment `labels`
+[labels]
+#!<name> = """#
+        .to_string();
+    assert_eq!(res, text);
+
+    let mut servers = BTreeMap::new();
+    servers.insert(
+        "alpha".to_string(),
+        Server {
+            host: "a.local".to_string(),
+            port: 80,
+        },
+    );
+    servers.insert(
+        "beta".to_string(),
+        Server {
+            host: "b.local".to_string(),
+            port: 81,
+        },
+    );
+    let mut labels = HashMap::new();
+    labels.insert("env".to_string(), "prod".to_string());
+    let test = Test {
+        a: 1,
+        servers,
+        labels,
+    };
+    let res = test.clone().into_string().unwrap();
+    println!("{res}");
+    let text = r#"# comment `Test`
+
+# comment `a`
+a = 1
+
+# comment `servers`
+[servers.alpha]
+# comment `host`
+host = "a.local"
+# comment `port`
+port = 80
+
+# comment `servers`
+[servers.beta]
+# comment `host`
+host = "b.local"
+# comment `port`
+port = 81
+
+# comment `labels`
+[labels]
+env = "prod""#
+        .to_string();
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(&text).unwrap();
+    assert_eq!(test, test1);
+}
+
+#[test]
+fn test_map_quoted_keys() {
+    /// comment `Server`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Server {
+        /// comment `port`
+        port: u16,
+    }
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `servers`
+        servers: BTreeMap<String, Server>,
+        /// comment `labels`
+        labels: HashMap<String, String>,
+    }
+    let test = Test {
+        servers: BTreeMap::from([
+            ("a.b".to_string(), Server { port: 80 }),
+            ("my server".to_string(), Server { port: 81 }),
+        ]),
+        labels: HashMap::from([("k.z".to_string(), "v".to_string())]),
+    };
+    let res = test.clone().into_string().unwrap();
+    println!("{res}");
+    let text = r#"# comment `servers`
+[servers."a.b"]
+# comment `port`
+port = 80
+
+# comment `servers`
+[servers."my server"]
+# comment `port`
+port = 81
+
+# comment `labels`
+[labels]
+"k.z" = "v""#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(&res).unwrap();
+    assert_eq!(test, test1);
+
+    let value = TomlContent::parse(Test::schema().unwrap(), &res).unwrap();
+    let mut content = Test::default().into_content().unwrap();
+    assert!(content.merge_value(value).is_empty());
+    assert_eq!(content.render().unwrap(), text);
+}
+
+#[test]
+fn test_nested_map() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `nested`
+        nested: BTreeMap<String, BTreeMap<String, u32>>,
+    }
+    let test = Test {
+        nested: BTreeMap::from([(
+            "o".to_string(),
+            BTreeMap::from([("k".to_string(), 5), ("l".to_string(), 6)]),
+        )]),
+    };
+    let res = test.clone().into_string().unwrap();
+    let text = r#"# comment `nested`
+[nested.o]
+k = 5
+l = 6"#;
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(&res).unwrap();
+    assert_eq!(test, test1);
+
+    let value = TomlContent::parse(Test::schema().unwrap(), &res).unwrap();
+    let mut content = Test::default().into_content().unwrap();
+    assert!(content.merge_value(value).is_empty());
+    assert_eq!(content.render().unwrap(), text);
+
+    let res = Test::schema_to_string().unwrap();
+    let text = r#"# comment `nested`
+#![nested.<name>]
+#!<name> = 0"#;
+    assert_eq!(res, text);
+}
+
+#[test]
+fn test_generic() {
+    /// comment `Pool`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Pool<B> {
+        /// comment `size`
+        size: usize,
+        /// comment `backend`
+        backend: B,
+    }
+    /// comment `Backend`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Backend {
+        /// comment `url`
+        url: String,
+    }
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[toml_input(bound = "T: TomlInput + Default")]
+    struct Test<'a, T>
+    where
+        T: Clone,
+    {
+        /// comment `a`
+        a: T,
+        #[serde(skip)]
+        marker: PhantomData<&'a ()>,
+    }
+    let test = Pool {
+        size: 2,
+        backend: Backend {
+            url: "redis://localhost".to_string(),
+        },
+    };
+    let res = test.clone().into_string().unwrap();
+    println!("{res}");
+    let text = r#"# comment `Pool`
+
+# comment `size`
+size = 2
+
+# comment `backend`
+[backend]
+# comment `url`
+url = "redis://localhost""#
+        .to_string();
+    assert_eq!(res, text);
+    let test1: Pool<Backend> = toml::from_str(&text).unwrap();
+    assert_eq!(test, test1);
+
+    let res = Test::<u32>::schema_to_string().unwrap();
+    let text = "# comment `Test`
+
+# comment `a`
+a = 0"
+        .to_string();
+    assert_eq!(res, text);
+}
+
+#[test]
+fn test_newtype() {
+    /// comment `Port`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq)]
+    struct Port(u16);
+    impl Default for Port {
+        fn default() -> Self {
+            Port(8080)
+        }
+    }
+    /// comment `Host`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(transparent)]
+    struct Host {
+        name: String,
+    }
+    /// comment `Range`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Range(u16, u16);
+    /// comment `Marker`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Marker;
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        port: Port,
+        host: Host,
+        range: Range,
+        marker: Marker,
+    }
+    let res = Test::schema_to_string().unwrap();
+    println!("{res}");
+    let text = r#"# comment `Test`
+
+# comment `Port`
+port = 8080
+# comment `Host`
+host = ""
+# comment `Range`
+range = [0, 0]
+
+# comment `Marker`
+[marker]"#
+        .to_string();
+    assert_eq!(res, text);
+
+    let test = Test {
+        port: Port(80),
+        host: Host {
+            name: "localhost".to_string(),
+        },
+        range: Range(1, 2),
+        marker: Marker,
+    };
+    let res = test.clone().into_string().unwrap();
+    let text = r#"# comment `Test`
+
+# comment `Port`
+port = 80
+# comment `Host`
+host = "localhost"
+# comment `Range`
+range = [1, 2]
+
+# comment `Marker`
+[marker]"#
+        .to_string();
+    assert_eq!(res, text);
+}
+
+#[test]
+fn test_serde_attrs() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default, rename_all = "camelCase")]
+    struct Test {
+        /// comment `max_size`
+        max_size: usize,
+        /// comment `type`
+        r#type: String,
+        /// comment `host`
+        #[serde(
+            rename(serialize = "hostName", deserialize = "host"),
+            alias = "hostName"
+        )]
+        host: String,
+        /// comment `label`
+        #[serde(skip_serializing_if = "String::is_empty")]
+        label: String,
+        /// skipped
+        #[serde(skip_serializing)]
+        cache: usize,
+    }
+    let test = Test {
+        max_size: 2,
+        r#type: "a".to_string(),
+        host: "localhost".to_string(),
+        label: String::new(),
+        cache: 3,
+    };
+    let res = test.clone().into_string().unwrap();
+    let text = r#"# comment `Test`
+
+# comment `max_size`
+maxSize = 2
+# comment `type`
+type = "a"
+# comment `host`
+hostName = "localhost"
+# comment `label`
+#!label = """#
+        .to_string();
+    assert_eq!(res, text);
+    let test1: Test = toml::from_str(&text).unwrap();
+    assert_eq!(Test { cache: 0, ..test }, test1);
+}
+
+#[test]
+fn test_default() {
+    fn default_port() -> u16 {
+        8080
+    }
+    fn default_host() -> Option<String> {
+        Some("localhost".to_string())
+    }
+    fn default_test() -> Test {
+        Test {
+            a: 5,
+            port: 1,
+            server: Server {
+                port: 9000,
+                name: "main".to_string(),
+                host: None,
+            },
+        }
+    }
+    /// comment `Server`
+    #[derive(Debug, TomlInput, Serialize, Deserialize, PartialEq)]
+    struct Server {
+        /// comment `port`
+        #[serde(default = "default_port")]
+        port: u16,
+        /// comment `name`
+        #[serde(default)]
+        name: String,
+        /// comment `host`
+        #[serde(default = "default_host")]
+        host: Option<String>,
+    }
+    let res = Server::schema_to_string().unwrap();
+    let text = r#"# comment `Server`
+
+# comment `port`
+port = 8080
+# comment `name`
+name = ""
+# comment `host`
+host = "localhost""#
+        .to_string();
+    assert_eq!(res, text);
+    let server: Server = toml::from_str("").unwrap();
+    assert_eq!(server.port, 8080);
+
+    /// comment `Test`
+    #[derive(Debug, TomlInput, Serialize, Deserialize, PartialEq)]
+    #[serde(default = "default_test")]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `port`
+        #[serde(default = "default_port")]
+        port: u16,
+        /// comment `server`
+        server: Server,
+    }
+    let res = Test::schema_to_string().unwrap();
+    let text = r#"# comment `Test`
+
+# comment `a`
+a = 5
+# comment `port`
+port = 8080
+
+# comment `server`
+[server]
+# comment `port`
+port = 9000
+# comment `name`
+name = "main"
+# comment `host`
+host = "localhost""#
+        .to_string();
+    assert_eq!(res, text);
+    let test: Test = toml::from_str("").unwrap();
+    assert_eq!(test.port, 8080);
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use toml_input::{
+    TomlInput,
+    validate::{Diagnostic, DiagnosticKind},
+};
+
+#[test]
+fn test_validate() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `a`
+        a: u8,
+        /// comment `b`
+        b: TestEnum,
+        /// comment `c`
+        c: Inner,
+        /// comment `d`
+        d: Vec<Inner>,
+        /// comment `e`
+        e: BTreeMap<String, bool>,
+    }
+    /// comment `TestEnum`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum TestEnum {
+        /// comment `A`
+        #[default]
+        A,
+        /// comment `B`
+        B,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `f`
+        f: String,
+    }
+    let schema = Test::schema().unwrap();
+    let text = Test::default().into_string().unwrap();
+    assert_eq!(schema.validate(&text), Vec::new());
+
+    let text = r#"a = 300
+b = "C"
+g = 1
+e = { x = true, y = "no" }
+d = { f = "" }
+
+[c]
+f = 1
+h = 2
+"#;
+    let diagnostics = schema.validate(text);
+    let found: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.kind,
+                diagnostic.key.as_str(),
+                diagnostic.line,
+                diagnostic.column,
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (DiagnosticKind::TypeMismatch, "a", 1, 5),
+            (DiagnosticKind::InvalidVariant, "b", 2, 5),
+            (DiagnosticKind::UnknownKey, "g", 3, 1),
+            (DiagnosticKind::TypeMismatch, "e.y", 4, 21),
+            (DiagnosticKind::WrongShape, "d", 5, 5),
+            (DiagnosticKind::TypeMismatch, "c.f", 8, 5),
+            (DiagnosticKind::UnknownKey, "c.h", 9, 1),
+        ]
+    );
+    assert_eq!(diagnostics[0].expected, "u8");
+    assert_eq!(diagnostics[0].docs, "comment `a`");
+    assert_eq!(diagnostics[1].expected, r#""A", "B""#);
+    assert_eq!(diagnostics[6].expected, "`f`");
+    assert_eq!(diagnostics[4].expected, "an array of tables of Inner");
+    assert_eq!(
+        diagnostics[1].to_string(),
+        r#"2:5: `b` = "C" is not a variant of TestEnum; expected "A", "B" (comment `b`)"#
+    );
+}
+
+#[test]
+fn test_validate_syntax() {
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        a: i32,
+    }
+    let diagnostics = Test::schema().unwrap().validate("a = 1\nb = ");
+    assert_eq!(diagnostics.len(), 1);
+    let Diagnostic {
+        kind, line, column, ..
+    } = &diagnostics[0];
+    assert_eq!((*kind, *line, *column), (DiagnosticKind::Syntax, 2, 5));
+}
+
+#[test]
+fn test_validate_suggestion() {
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        port: u16,
+        log_level: LogLevel,
+    }
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(rename_all = "lowercase")]
+    enum LogLevel {
+        #[default]
+        Info,
+        Warning,
+        Error,
+    }
+    let text = "prot = 80\nlog_level = \"warnig\"\nxyz = 1";
+    let diagnostics = Test::schema().unwrap().validate(text);
+    let suggestions: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.suggestion.as_deref())
+        .collect();
+    assert_eq!(suggestions, vec![Some("port"), Some("warning"), None]);
+    assert_eq!(
+        diagnostics[0].message,
+        "unknown key `prot`, did you mean `port`?"
+    );
+}
+
+#[test]
+fn test_validate_alias() {
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        #[serde(alias = "lvl", alias = "lvl2")]
+        level: u8,
+        inner: Inner,
+    }
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        #[serde(alias = "p")]
+        port: u16,
+    }
+    let schema = Test::schema().unwrap();
+    let text = "lvl2 = 3\n\n[inner]\np = 80";
+    assert_eq!(schema.validate(text), Vec::new());
+    let _: Test = toml::from_str(text).unwrap();
+
+    let diagnostics = schema.validate("lvl2 = 300\nlvl3 = 1");
+    let found: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.kind, diagnostic.key.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (DiagnosticKind::TypeMismatch, "lvl2"),
+            (DiagnosticKind::UnknownKey, "lvl3"),
+        ]
+    );
+}
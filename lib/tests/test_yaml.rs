@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use toml_input::TomlInput;
+
+#[test]
+fn test_yaml() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `a`
+        a: i32,
+        /// comment `b`
+        b: Option<String>,
+        /// comment `c`
+        c: Mode,
+        /// comment `inner`
+        inner: Inner,
+        /// comment `items`
+        items: Vec<Item>,
+        /// comment `tags`
+        tags: HashMap<String, u32>,
+        /// comment `servers`
+        servers: BTreeMap<String, Item>,
+    }
+    /// comment `Mode`
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    enum Mode {
+        /// comment `Fast`
+        Fast,
+        /// comment `Slow`
+        #[default]
+        Slow,
+    }
+    /// comment `Inner`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Inner {
+        /// comment `d`
+        d: f64,
+        /// comment `e`
+        e: Option<bool>,
+    }
+    /// comment `Item`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Item {
+        /// comment `name`
+        name: String,
+    }
+
+    let test = Test {
+        a: 1,
+        b: None,
+        c: Mode::Fast,
+        inner: Inner { d: 0.5, e: None },
+        items: vec![
+            Item {
+                name: "first \"one\"".to_string(),
+            },
+            Item {
+                name: "second".to_string(),
+            },
+        ],
+        tags: HashMap::from([("x".to_string(), 1)]),
+        servers: BTreeMap::from([(
+            "alpha".to_string(),
+            Item {
+                name: "a".to_string(),
+            },
+        )]),
+    };
+    let text = test.clone().into_content().unwrap().render_yaml().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `a`
+a: 1
+# comment `b`
+#!b: ""
+# comment `Fast`
+c: "Fast"
+# comment `Slow`
+#!c: "Slow"
+
+# comment `inner`
+inner:
+  # comment `d`
+  d: 0.5
+  # comment `e`
+  #!e: false
+
+# comment `items`
+items:
+  # comment `name`
+  - name: "first \"one\""
+
+  # comment `name`
+  - name: "second"
+
+# comment `tags`
+tags:
+  x: 1
+
+# comment `servers`
+servers:
+  alpha:
+    # comment `name`
+    name: "a""#;
+    assert_eq!(res, text);
+    let test1: Test = serde_yaml::from_str(&text).unwrap();
+    assert_eq!(test, test1);
+
+    let text = Test::default()
+        .into_content()
+        .unwrap()
+        .render_yaml()
+        .unwrap();
+    println!("{}", text);
+    let test1: Test = serde_yaml::from_str(&text).unwrap();
+    assert_eq!(Test::default(), test1);
+}
+
+#[test]
+fn test_yaml_variant() {
+    /// comment `Test`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    struct Test {
+        /// comment `backend`
+        backend: Backend,
+        /// comment `mode`
+        mode: Mode,
+    }
+    /// comment `Backend`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(tag = "type")]
+    #[allow(dead_code)]
+    enum Backend {
+        /// comment `Local`
+        #[default]
+        Local,
+        /// comment `S3`
+        S3 {
+            /// comment `bucket`
+            bucket: String,
+        },
+    }
+    /// comment `Mode`
+    #[derive(Debug, Clone, TomlInput, Serialize, Deserialize, PartialEq, Default)]
+    #[allow(dead_code)]
+    enum Mode {
+        /// comment `Off`
+        #[default]
+        Off,
+        /// comment `Tcp`
+        Tcp {
+            /// comment `port`
+            port: u16,
+        },
+        /// comment `Unix`
+        Unix(String),
+        /// comment `Pair`
+        Pair(u8, String),
+    }
+
+    let test = Test {
+        backend: Backend::S3 {
+            bucket: "data".to_string(),
+        },
+        mode: Mode::Tcp { port: 80 },
+    };
+    let text = test.clone().into_content().unwrap().render_yaml().unwrap();
+    println!("{}", text);
+    let res = r#"# comment `Test`
+
+# comment `Off`
+#!mode: "Off"
+# comment `Unix`
+#!mode: !Unix ""
+# comment `Pair`
+#!mode: !Pair [0, ""]
+
+# comment `Local`
+#!backend:
+  #!type: "Local"
+
+# comment `S3`
+backend:
+  type: "S3"
+  # comment `bucket`
+  bucket: "data"
+
+# comment `Tcp`
+mode: !Tcp
+  # comment `port`
+  port: 80"#;
+    assert_eq!(res, text);
+    let test1: Test = serde_yaml::from_str(&text).unwrap();
+    assert_eq!(test, test1);
+
+    let text = Test::default()
+        .into_content()
+        .unwrap()
+        .render_yaml()
+        .unwrap();
+    println!("{}", text);
+    let test1: Test = serde_yaml::from_str(&text).unwrap();
+    assert_eq!(Test::default(), test1);
+
+    let modes = [
+        (Mode::Unix("/s".to_string()), "mode: !Unix \"/s\""),
+        (Mode::Pair(1, "a".to_string()), "mode: !Pair [1, \"a\"]"),
+    ];
+    for (mode, line) in modes {
+        let test = Test {
+            mode,
+            ..Default::default()
+        };
+        let text = test.clone().into_content().unwrap().render_yaml().unwrap();
+        assert!(text.contains(&format!("\n{line}\n")));
+        let test1: Test = serde_yaml::from_str(&text).unwrap();
+        assert_eq!(test, test1);
+    }
+}